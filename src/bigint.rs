@@ -0,0 +1,403 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+// arbitrary-precision integer, stored as a sign and a magnitude of base 2^32 digits
+// with the least significant digit first. The magnitude never has trailing zeros,
+// so zero is an empty vector and is never negative.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    digits: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        Self {
+            negative: false,
+            digits: Vec::new(),
+        }
+    }
+
+    fn from_parts(negative: bool, mut digits: Vec<u32>) -> Self {
+        trim(&mut digits);
+        let negative = negative && !digits.is_empty();
+        Self { negative, digits }
+    }
+
+    // parses a string of decimal digits, returns None if it contains anything else
+    pub fn parse(str: &str) -> Option<Self> {
+        if str.is_empty() {
+            return None;
+        }
+
+        let mut digits = Vec::new();
+        for c in str.chars() {
+            let digit = c.to_digit(10)?;
+            mul_add_small(&mut digits, 10, digit);
+        }
+        Some(Self::from_parts(false, digits))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    // upper bound for the number of bits in the magnitude
+    pub fn bit_len(&self) -> u64 {
+        self.digits.len() as u64 * 32
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.digits.len() > 2 {
+            return None;
+        }
+
+        let mut magnitude: u64 = 0;
+        for digit in self.digits.iter().rev() {
+            magnitude = (magnitude << 32) | *digit as u64;
+        }
+
+        if self.negative {
+            if magnitude <= i64::MAX as u64 + 1 {
+                Some((magnitude as i64).wrapping_neg())
+            } else {
+                None
+            }
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    // the exact value of a float without a fraction, None for anything else
+    pub fn from_f64(num: f64) -> Option<Self> {
+        if !num.is_finite() || num.fract() != 0. {
+            return None;
+        }
+        if num == 0. {
+            return Some(Self::zero());
+        }
+
+        // num is mantissa * 2^exponent, and the mantissa fits in 53 bits
+        let bits = num.to_bits();
+        let raw_exponent = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = bits & ((1 << 52) - 1);
+        let (mantissa, exponent) = match raw_exponent {
+            0 => (fraction, -1074),
+            _ => (fraction | (1 << 52), raw_exponent - 1075),
+        };
+
+        let magnitude = if exponent >= 0 {
+            &BigInt::from(mantissa as i64) * &BigInt::from(2).pow(exponent as u32)
+        } else {
+            BigInt::from((mantissa >> -exponent) as i64)
+        };
+        Some(if num < 0. { -&magnitude } else { magnitude })
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let mut num = 0.;
+        for digit in self.digits.iter().rev() {
+            num = num * 4294967296. + *digit as f64;
+        }
+
+        if self.negative {
+            -num
+        } else {
+            num
+        }
+    }
+
+    // division that rounds towards negative infinity, returns None when dividing by zero
+    pub fn div_rem_floor(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+
+        let (quotient, remainder) = div_rem_magnitude(&self.digits, &other.digits);
+        let quotient = BigInt::from_parts(self.negative != other.negative, quotient);
+        let remainder = BigInt::from_parts(self.negative, remainder);
+
+        if !remainder.is_zero() && remainder.negative != other.negative {
+            Some((&quotient - &BigInt::from(1), &remainder + other))
+        } else {
+            Some((quotient, remainder))
+        }
+    }
+
+    pub fn pow(&self, mut exp: u32) -> BigInt {
+        let mut base = self.clone();
+        let mut result = BigInt::from(1);
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    // applies a bitwise operator digit by digit, as if both numbers were stored in two's complement
+    pub fn bitwise(&self, other: &BigInt, op: fn(u32, u32) -> u32) -> BigInt {
+        let len = self.digits.len().max(other.digits.len()) + 1;
+        let a = self.to_twos_complement(len);
+        let b = other.to_twos_complement(len);
+
+        let digits: Vec<u32> = a.iter().zip(b).map(|(a, b)| op(*a, b)).collect();
+        if digits[len - 1] >> 31 == 1 {
+            let magnitude = negate_twos_complement(digits);
+            BigInt::from_parts(true, magnitude)
+        } else {
+            BigInt::from_parts(false, digits)
+        }
+    }
+
+    fn to_twos_complement(&self, len: usize) -> Vec<u32> {
+        let mut digits = self.digits.clone();
+        digits.resize(len, 0);
+
+        if self.negative {
+            negate_twos_complement(digits)
+        } else {
+            digits
+        }
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(int: i64) -> Self {
+        let magnitude = int.unsigned_abs();
+        let digits = vec![magnitude as u32, (magnitude >> 32) as u32];
+        Self::from_parts(int < 0, digits)
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        // split off chunks of 9 decimal digits, the least significant chunk first
+        let mut chunks = Vec::new();
+        let mut digits = self.digits.clone();
+        while !digits.is_empty() {
+            chunks.push(div_rem_small(&mut digits, 1_000_000_000));
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks[chunks.len() - 1])?;
+        for chunk in chunks.iter().rev().skip(1) {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.digits, &other.digits),
+            (true, true) => cmp_magnitude(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.digits.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitude(&self.digits, &other.digits));
+        }
+
+        // the signs differ, so subtract the smaller magnitude from the larger one
+        match cmp_magnitude(&self.digits, &other.digits) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, sub_magnitude(&other.digits, &self.digits))
+            }
+            _ => BigInt::from_parts(self.negative, sub_magnitude(&self.digits, &other.digits)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &(-other)
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            mul_magnitude(&self.digits, &other.digits),
+        )
+    }
+}
+
+fn negate_twos_complement(mut digits: Vec<u32>) -> Vec<u32> {
+    let mut carry = 1;
+    for digit in digits.iter_mut() {
+        let sum = (!*digit) as u64 + carry;
+        *digit = sum as u32;
+        carry = sum >> 32;
+    }
+    digits
+}
+
+fn trim(digits: &mut Vec<u32>) {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        out.push(sum as u32);
+        carry = sum >> 32;
+    }
+    out.push(carry as u32);
+
+    trim(&mut out);
+    out
+}
+
+// expects a >= b
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+
+    for (i, digit) in a.iter().enumerate() {
+        let sub = *b.get(i).unwrap_or(&0) as i64 + borrow;
+        let mut diff = *digit as i64 - sub;
+        if diff < 0 {
+            diff += 1 << 32;
+            borrow = 1;
+        } else {
+            borrow = 0;
+        }
+        out.push(diff as u32);
+    }
+
+    trim(&mut out);
+    out
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+
+    let mut out = vec![0u32; a.len() + b.len()];
+    for (i, digit_a) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, digit_b) in b.iter().enumerate() {
+            let product = *digit_a as u64 * *digit_b as u64 + out[i + j] as u64 + carry;
+            out[i + j] = product as u32;
+            carry = product >> 32;
+        }
+        out[i + b.len()] = carry as u32;
+    }
+
+    trim(&mut out);
+    out
+}
+
+fn mul_add_small(digits: &mut Vec<u32>, mul: u32, add: u32) {
+    let mut carry = add as u64;
+    for digit in digits.iter_mut() {
+        let product = *digit as u64 * mul as u64 + carry;
+        *digit = product as u32;
+        carry = product >> 32;
+    }
+    if carry > 0 {
+        digits.push(carry as u32);
+    }
+}
+
+// divides the digits in place and returns the remainder
+fn div_rem_small(digits: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut remainder: u64 = 0;
+    for digit in digits.iter_mut().rev() {
+        let current = (remainder << 32) | *digit as u64;
+        *digit = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+
+    trim(digits);
+    remainder as u32
+}
+
+// long division one bit at a time, the divisor must not be zero
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_magnitude(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+
+    if b.len() == 1 {
+        let mut quotient = a.to_vec();
+        let remainder = div_rem_small(&mut quotient, b[0]);
+        let mut remainder = vec![remainder];
+        trim(&mut remainder);
+        return (quotient, remainder);
+    }
+
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+
+    for i in (0..a.len() * 32).rev() {
+        // remainder = remainder * 2 + next bit of a
+        mul_add_small(&mut remainder, 2, (a[i / 32] >> (i % 32)) & 1);
+
+        if cmp_magnitude(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, b);
+            quotient[i / 32] |= 1 << (i % 32);
+        }
+    }
+
+    trim(&mut quotient);
+    (quotient, remainder)
+}
@@ -1,8 +1,17 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    env, fs,
+    process::Command,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use crate::{
-    error::{rox_error, RuntimeErr},
+    bigint::BigInt,
+    environment::Env,
+    error::RuntimeErr,
+    generator::Generator,
     interpreter::Interpreter,
+    iterator::{LinesIter, RoxIterator},
     stmt::Stmt,
     token::Token,
     value::Value,
@@ -16,6 +25,21 @@ pub trait Callable: std::fmt::Debug + CallableClone {
     ) -> Result<Value, RuntimeErr>;
     fn arity(&self) -> usize;
     fn to_string(&self) -> String;
+
+    // the line the function was declared on, builtins aren't declared anywhere
+    fn line(&self) -> Option<usize> {
+        None
+    }
+
+    // the instance a method is bound to, the same method of two instances isn't the same value
+    fn instance_id(&self) -> Option<usize> {
+        None
+    }
+
+    // only functions declared with 'proces' can be called in tail position without recursing
+    fn as_function(&self) -> Option<&FunDeclaration> {
+        None
+    }
 }
 impl Clone for Box<dyn Callable> {
     fn clone(&self) -> Box<dyn Callable> {
@@ -60,17 +84,134 @@ impl Callable for Clock {
 #[derive(Debug, Clone)]
 pub struct Len;
 impl Callable for Len {
-    fn call(&self, arguments: Vec<Value>, _: &mut Interpreter) -> Result<Value, RuntimeErr> {
+    fn call(
+        &self,
+        arguments: Vec<Value>,
+        interpreter: &mut Interpreter,
+    ) -> Result<Value, RuntimeErr> {
         let result = match &arguments[0] {
             Value::List(values) => values.len(),
+            // anything else that can be iterated over is counted, which uses up iterators
+            value => match value.iter() {
+                Some(iterator) => {
+                    let mut count = 0;
+                    while iterator.next(0, interpreter)?.is_some() {
+                        count += 1;
+                    }
+                    count
+                }
+                None => {
+                    return Err(RuntimeErr::Err(
+                        0,
+                        "Je kan len(list) alleen gebruiken op lists.".to_string(),
+                    ))
+                }
+            },
+        };
+        Ok(Value::Int(result as i64))
+    }
+
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn to_string(&self) -> String {
+        "lengte".to_string()
+    }
+}
+
+fn iter_argument(value: &Value, function: &str) -> Result<RoxIterator, RuntimeErr> {
+    match value.iter() {
+        Some(iterator) => Ok(iterator),
+        None => {
+            let msg = format!("Je kan {} niet gebruiken op '{}'.", function, value);
+            Err(RuntimeErr::Err(0, msg))
+        }
+    }
+}
+
+// collects all values of a list, string or iterator into a new list
+#[derive(Debug, Clone)]
+pub struct Collect;
+impl Callable for Collect {
+    fn call(
+        &self,
+        arguments: Vec<Value>,
+        interpreter: &mut Interpreter,
+    ) -> Result<Value, RuntimeErr> {
+        let iterator = iter_argument(&arguments[0], "verzamel")?;
+
+        let mut values = Vec::new();
+        while let Some(value) = iterator.next(0, interpreter)? {
+            values.push(value);
+        }
+        Ok(Value::List(values))
+    }
+
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn to_string(&self) -> String {
+        "verzamel".to_string()
+    }
+}
+
+// collects at most n values, which also works on iterators that never end
+#[derive(Debug, Clone)]
+pub struct Take;
+impl Callable for Take {
+    fn call(
+        &self,
+        arguments: Vec<Value>,
+        interpreter: &mut Interpreter,
+    ) -> Result<Value, RuntimeErr> {
+        let iterator = iter_argument(&arguments[0], "neem")?;
+        let n = match arguments[1] {
+            Value::Int(int) if int >= 0 => int,
             _ => {
                 return Err(RuntimeErr::Err(
                     0,
-                    "Je kan len(list) alleen gebruiken op lists.".to_string(),
+                    "Je kan neem(x, n) alleen gebruiken met een positief geheel getal.".to_string(),
                 ))
             }
         };
-        Ok(Value::Num(result as f64))
+
+        let mut values = Vec::new();
+        for _ in 0..n {
+            match iterator.next(0, interpreter)? {
+                Some(value) => values.push(value),
+                None => break,
+            }
+        }
+        Ok(Value::List(values))
+    }
+
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn to_string(&self) -> String {
+        "neem".to_string()
+    }
+}
+
+// the next value of an iterator, or niks if there are no values left
+#[derive(Debug, Clone)]
+pub struct Next;
+impl Callable for Next {
+    fn call(
+        &self,
+        arguments: Vec<Value>,
+        interpreter: &mut Interpreter,
+    ) -> Result<Value, RuntimeErr> {
+        let Value::Iterator(iterator) = &arguments[0] else {
+            return Err(RuntimeErr::Err(
+                0,
+                "Je kan volgende(x) alleen gebruiken op iterators.".to_string(),
+            ));
+        };
+        Ok(iterator.next(0, interpreter)?.unwrap_or(Value::Nil))
     }
 
     fn arity(&self) -> usize {
@@ -78,28 +219,217 @@ impl Callable for Len {
     }
 
     fn to_string(&self) -> String {
-        "lengte".to_string()
+        "volgende".to_string()
     }
 }
 
+// the lines of a file, read one at a time
+#[derive(Debug, Clone)]
+pub struct Lines;
+impl Callable for Lines {
+    fn call(&self, arguments: Vec<Value>, _: &mut Interpreter) -> Result<Value, RuntimeErr> {
+        let path = str_argument(&arguments[0], "regels(pad)")?;
+        let lines = LinesIter::open(path)?;
+        Ok(Value::Iterator(RoxIterator::new(lines)))
+    }
+
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn to_string(&self) -> String {
+        "regels".to_string()
+    }
+}
+
+// the whole file as one string
+#[derive(Debug, Clone)]
+pub struct ReadFile;
+impl Callable for ReadFile {
+    fn call(&self, arguments: Vec<Value>, _: &mut Interpreter) -> Result<Value, RuntimeErr> {
+        let path = str_argument(&arguments[0], "lees(pad)")?;
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(Value::Str(contents)),
+            Err(err) => Err(RuntimeErr::Err(
+                0,
+                format!("Kon '{}' niet lezen: {}.", path, err),
+            )),
+        }
+    }
+
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn to_string(&self) -> String {
+        "lees".to_string()
+    }
+}
+
+// replaces the file with the text, or creates it
+#[derive(Debug, Clone)]
+pub struct WriteFile;
+impl Callable for WriteFile {
+    fn call(&self, arguments: Vec<Value>, _: &mut Interpreter) -> Result<Value, RuntimeErr> {
+        let path = str_argument(&arguments[0], "schrijf(pad, tekst)")?;
+        match fs::write(path, arguments[1].to_string()) {
+            Ok(()) => Ok(Value::Nil),
+            Err(err) => Err(RuntimeErr::Err(
+                0,
+                format!("Kon '{}' niet schrijven: {}.", path, err),
+            )),
+        }
+    }
+
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn to_string(&self) -> String {
+        "schrijf".to_string()
+    }
+}
+
+// the value of an environment variable, or niks if it isn't set
+#[derive(Debug, Clone)]
+pub struct EnvVar;
+impl Callable for EnvVar {
+    fn call(&self, arguments: Vec<Value>, _: &mut Interpreter) -> Result<Value, RuntimeErr> {
+        let name = str_argument(&arguments[0], "omgeving(naam)")?;
+        match env::var(name) {
+            Ok(value) => Ok(Value::Str(value)),
+            Err(_) => Ok(Value::Nil),
+        }
+    }
+
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn to_string(&self) -> String {
+        "omgeving".to_string()
+    }
+}
+
+// runs a program with a list of arguments and returns what it printed
+#[derive(Debug, Clone)]
+pub struct RunCommand;
+impl Callable for RunCommand {
+    fn call(&self, arguments: Vec<Value>, _: &mut Interpreter) -> Result<Value, RuntimeErr> {
+        let program = str_argument(&arguments[0], "commando(programma, argumenten)")?;
+        let Value::List(args) = &arguments[1] else {
+            return Err(RuntimeErr::Err(
+                0,
+                "De argumenten van commando moeten een lijst zijn.".to_string(),
+            ));
+        };
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+
+        match Command::new(program).args(args).output() {
+            Ok(output) => Ok(Value::Str(
+                String::from_utf8_lossy(&output.stdout).into_owned(),
+            )),
+            Err(err) => Err(RuntimeErr::Err(
+                0,
+                format!("Kon '{}' niet starten: {}.", program, err),
+            )),
+        }
+    }
+
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn to_string(&self) -> String {
+        "commando".to_string()
+    }
+}
+
+fn str_argument<'a>(value: &'a Value, function: &str) -> Result<&'a str, RuntimeErr> {
+    match value {
+        Value::Str(str) => Ok(str),
+        _ => {
+            let msg = format!("Je kan {} alleen gebruiken op een reeks.", function);
+            Err(RuntimeErr::Err(0, msg))
+        }
+    }
+}
+
+// fails unless the argument is wellus
+#[derive(Debug, Clone)]
+pub struct Assert;
+impl Callable for Assert {
+    fn call(&self, arguments: Vec<Value>, _: &mut Interpreter) -> Result<Value, RuntimeErr> {
+        match &arguments[0] {
+            Value::True => Ok(Value::Nil),
+            actual => Err(assertion_err(&Value::True, actual)),
+        }
+    }
+
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn to_string(&self) -> String {
+        "verzeker".to_string()
+    }
+}
+
+// fails unless both arguments are equal, the expected value comes first
+#[derive(Debug, Clone)]
+pub struct AssertEqual;
+impl Callable for AssertEqual {
+    fn call(&self, arguments: Vec<Value>, _: &mut Interpreter) -> Result<Value, RuntimeErr> {
+        let (expected, actual) = (&arguments[0], &arguments[1]);
+        if Value::is_equal(expected, actual) {
+            Ok(Value::Nil)
+        } else {
+            Err(assertion_err(expected, actual))
+        }
+    }
+
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn to_string(&self) -> String {
+        "verzekerGelijk".to_string()
+    }
+}
+
+fn assertion_err(expected: &Value, actual: &Value) -> RuntimeErr {
+    let msg = format!("Verwachtte '{}' maar kreeg '{}'.", expected, actual);
+    RuntimeErr::Err(0, msg)
+}
+
 #[derive(Debug, Clone)]
 pub struct Factorial;
 impl Callable for Factorial {
-    fn call(&self, arguments: Vec<Value>, _: &mut Interpreter) -> Result<Value, RuntimeErr> {
-        let mut result = match arguments[0] {
-            Value::Num(num) => num,
+    fn call(
+        &self,
+        arguments: Vec<Value>,
+        interpreter: &mut Interpreter,
+    ) -> Result<Value, RuntimeErr> {
+        let n = match arguments[0] {
+            Value::Int(int) if int >= 0 => int,
             _ => {
                 return Err(RuntimeErr::Err(
                     0,
-                    "Je kan fact(n) alleen gebruiken op nummers.".to_string(),
+                    "Je kan fact(n) alleen gebruiken op niet-negatieve gehele getallen."
+                        .to_string(),
                 ))
             }
-        } as usize;
-        for i in 2..result {
-            result *= i;
+        };
+
+        // n! has at most log2(n) bits for every factor
+        interpreter.reserve_bits(n as u64 * (64 - n.leading_zeros()) as u64)?;
+        let mut result = BigInt::from(1);
+        for i in 2..=n {
+            interpreter.step()?;
+            result = &result * &BigInt::from(i);
         }
 
-        Ok(Value::Num(result as f64))
+        Ok(Value::from_big(result))
     }
 
     fn arity(&self) -> usize {
@@ -114,34 +444,38 @@ impl Callable for Factorial {
 #[derive(Debug, Clone)]
 pub struct Fibonacci;
 impl Callable for Fibonacci {
-    fn call(&self, arguments: Vec<Value>, _: &mut Interpreter) -> Result<Value, RuntimeErr> {
+    fn call(
+        &self,
+        arguments: Vec<Value>,
+        interpreter: &mut Interpreter,
+    ) -> Result<Value, RuntimeErr> {
         let n = match arguments[0] {
-            Value::Num(num) => num as u128,
+            Value::Int(int) if int >= 0 => int,
             _ => {
                 return Err(RuntimeErr::Err(
                     0,
-                    "Je kan fact(n) alleen gebruiken op nummers.".to_string(),
+                    "Je kan fib(n) alleen gebruiken op niet-negatieve gehele getallen.".to_string(),
                 ))
             }
         };
 
-        let mut a: u128 = 0;
-        let mut b: u128 = 1;
-
-        for _ in 0..n {
-            let temp = a;
-            a = b;
-            b = temp + b;
+        if n == 0 {
+            return Ok(Value::Int(0));
         }
 
-        let mut result = a as i64;
+        let mut a = BigInt::zero();
+        let mut b = BigInt::from(1);
 
-        if a > i64::max_value() as u128 {
-            result = i64::max_value();
-            rox_error(0, "overvloei is gebeurt in fib functie.");
+        // fib(n) grows by less than one bit per step
+        interpreter.reserve_bits(n as u64)?;
+        for _ in 1..n {
+            interpreter.step()?;
+            let next = &a + &b;
+            a = b;
+            b = next;
         }
 
-        Ok(Value::Num(result as f64))
+        Ok(Value::from_big(b))
     }
 
     fn arity(&self) -> usize {
@@ -157,34 +491,69 @@ impl Callable for Fibonacci {
 pub struct FunDeclaration {
     pub name: Token,
     pub params: Vec<Token>,
-    pub body: Vec<Stmt>,
+    // the types after ':', they are only used by the type checker
+    pub param_types: Vec<Option<Token>>,
+    pub return_type: Option<Token>,
+    pub body: Rc<Vec<Stmt>>,
+    pub is_generator: bool,
 }
-impl Callable for FunDeclaration {
-    fn call(
+impl FunDeclaration {
+    // 'this' is the instance that a method is called on, it is available as 'dit'
+    pub fn call_with(
         &self,
+        this: Option<Value>,
         arguments: Vec<Value>,
         interpreter: &mut Interpreter,
     ) -> Result<Value, RuntimeErr> {
-        interpreter.env.create_new_child();
+        let mut scope = self.scope(&this, arguments);
 
-        for i in 0..self.params.len() {
-            interpreter
-                .env
-                .insert_value(&self.params[i].lexeme, arguments[i].clone())
+        // the body of a generator only runs when its values are asked for
+        if self.is_generator {
+            let generator = Generator::new(self.name.clone(), scope, Rc::clone(&self.body));
+            return Ok(Value::Iterator(RoxIterator::new(generator)));
         }
 
-        for stmt in &self.body {
-            if let Err(e) = interpreter.evaluate_stmt(stmt) {
-                interpreter.env.kill_youngest_child();
-                match e {
-                    RuntimeErr::Return { value } => return Ok(value),
-                    RuntimeErr::Err(line, msg) => return Err(RuntimeErr::Err(line, msg)),
-                }
-            }
+        interpreter.call_stack.push(self.name.clone());
+        loop {
+            interpreter.env.push_child(scope);
+            let result = self
+                .body
+                .iter()
+                .try_for_each(|stmt| interpreter.evaluate_stmt(stmt));
+            interpreter.env.kill_youngest_child();
+
+            // a tail call runs the body again in a fresh scope, without growing the Rust stack
+            let Err(RuntimeErr::TailCall { arguments }) = result else {
+                interpreter.call_stack.pop();
+                return match result {
+                    Ok(()) => Ok(Value::Nil),
+                    Err(RuntimeErr::Return { value }) => Ok(value),
+                    Err(err) => Err(err),
+                };
+            };
+
+            scope = self.scope(&this, arguments);
         }
+    }
 
-        interpreter.env.kill_youngest_child();
-        Ok(Value::Nil)
+    fn scope(&self, this: &Option<Value>, arguments: Vec<Value>) -> Env {
+        let mut scope = Env::new();
+        if let Some(this) = this {
+            scope.insert_value(&"dit".to_string(), this.clone());
+        }
+        for (param, argument) in self.params.iter().zip(arguments) {
+            scope.insert_value(&param.lexeme, argument)
+        }
+        scope
+    }
+}
+impl Callable for FunDeclaration {
+    fn call(
+        &self,
+        arguments: Vec<Value>,
+        interpreter: &mut Interpreter,
+    ) -> Result<Value, RuntimeErr> {
+        self.call_with(None, arguments, interpreter)
     }
 
     fn arity(&self) -> usize {
@@ -194,4 +563,12 @@ impl Callable for FunDeclaration {
     fn to_string(&self) -> String {
         self.name.lexeme.clone()
     }
+
+    fn line(&self) -> Option<usize> {
+        Some(self.name.line)
+    }
+
+    fn as_function(&self) -> Option<&FunDeclaration> {
+        Some(self)
+    }
 }
@@ -0,0 +1,850 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    rc::Rc,
+};
+
+use crate::{
+    callable::FunDeclaration,
+    class::{operator_method, CONSTRUCTOR},
+    error::Diagnostic,
+    expr::Expr,
+    stmt::{If, Stmt},
+    token::{Literal, Token},
+    token_type::TokenType,
+};
+
+// static pass that checks the types of a program before it runs, with the annotations
+// of 'laat x: getal = 3;' and 'proces f(a: reeks): lijst { ... }'. It runs after the
+// resolver, so the names are known to be valid.
+//
+// The checking is gradual: a value whose type isn't known, like the result of a function
+// without a return type, fits everywhere. So a program without annotations is only
+// rejected for an operator that can never work on the types it gets, like "a" - 1.
+// A variable without an annotation has the type of its value, as long as nothing else
+// is ever assigned to its name. A function sees the variables of whoever calls it, so in
+// its body a name it doesn't declare itself is only known if no other scope declares that
+// name. The interpreter ignores the annotations
+pub struct Checker {
+    scopes: Vec<HashMap<String, Variable>>,
+    // the first scope of each function we're currently in
+    functions: Vec<usize>,
+    // how often each name is declared, anywhere in the program
+    declarations: HashMap<String, usize>,
+    // the names that are declared more than once
+    shadowed: HashSet<String>,
+    // the names that something is assigned to after their declaration, anywhere in the program
+    assigned: HashSet<String>,
+    // the classes that can be used as a type
+    classes: HashSet<String>,
+    // the name and return type of each function we're currently in
+    returns: Vec<(String, Type)>,
+    errors: Vec<Diagnostic>,
+}
+
+struct Variable {
+    ty: Type,
+    // everything that is assigned to it has to fit the annotation
+    annotation: Option<Type>,
+    // the parameters of a function or class, None once something is assigned to it
+    signature: Option<Rc<Signature>>,
+}
+
+struct Signature {
+    params: Vec<(String, Type)>,
+    returns: Type,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Type {
+    // not known before the program runs
+    Any,
+    Number,
+    Str,
+    Bool,
+    List,
+    Function,
+    Nil,
+    // an instance of the class with this name
+    Instance(String),
+}
+
+impl Checker {
+    // any of the globals that are already there could be a class
+    pub fn new(globals: Vec<String>) -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            functions: Vec::new(),
+            declarations: HashMap::new(),
+            shadowed: HashSet::new(),
+            assigned: HashSet::new(),
+            classes: globals.into_iter().collect(),
+            returns: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn check(mut self, statements: &[Stmt]) -> Result<(), Vec<Diagnostic>> {
+        // the first pass only finds the names that are assigned to or declared and the
+        // classes, so what is known about a name doesn't depend on where that happens
+        self.check_statements(statements);
+        self.shadowed = self
+            .declarations
+            .iter()
+            .filter(|(_, count)| **count > 1)
+            .map(|(name, _)| name.clone())
+            .collect();
+        self.errors.clear();
+        self.scopes = vec![HashMap::new()];
+        self.check_statements(statements);
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            self.errors.sort_by_key(|error| error.line);
+            Err(self.errors)
+        }
+    }
+
+    fn error(&mut self, line: usize, msg: String) {
+        self.errors.push(Diagnostic::new(line, msg));
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &Token, variable: Variable) {
+        *self.declarations.entry(name.lexeme.clone()).or_default() += 1;
+        let scope = self.scopes.last_mut().unwrap();
+        scope.insert(name.lexeme.clone(), variable);
+    }
+
+    // None for names that are declared later on, or by an earlier program, and for names
+    // from outside the current function that another scope might give a value when it runs
+    fn lookup(&self, name: &Token) -> Option<&Variable> {
+        let (depth, variable) = self
+            .scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(depth, scope)| Some((depth, scope.get(&name.lexeme)?)))?;
+
+        let own = self.functions.last().is_none_or(|start| depth >= *start);
+        (own || !self.shadowed.contains(&name.lexeme)).then_some(variable)
+    }
+
+    fn annotation(&mut self, token: &Token) -> Type {
+        match token.lexeme.as_str() {
+            "getal" => Type::Number,
+            "reeks" => Type::Str,
+            "waarheidswaarde" => Type::Bool,
+            "lijst" => Type::List,
+            "proces" => Type::Function,
+            "niks" => Type::Nil,
+            "waarde" => Type::Any,
+            name if self.classes.contains(name) => Type::Instance(name.to_string()),
+            name => {
+                let msg = format!("'{}' is geen type.", name);
+                self.error(token.line, msg);
+                Type::Any
+            }
+        }
+    }
+
+    fn optional_annotation(&mut self, token: &Option<Token>) -> Type {
+        match token {
+            Some(token) => self.annotation(token),
+            None => Type::Any,
+        }
+    }
+
+    // a function that nothing else is assigned to keeps its signature
+    fn signature(&mut self, function: &FunDeclaration) -> Signature {
+        let params = function
+            .params
+            .iter()
+            .zip(&function.param_types)
+            .map(|(param, ty)| (param.lexeme.clone(), self.optional_annotation(ty)))
+            .collect();
+
+        // calling a generator gives an iterator, whatever it gives back
+        let mut returns = self.optional_annotation(&function.return_type);
+        if function.is_generator {
+            returns = Type::Any;
+        }
+        Signature { params, returns }
+    }
+
+    fn check_fits(&mut self, name: &Token, value: &Type, annotation: &Type) {
+        if !value.fits(annotation) {
+            let msg = format!(
+                "'{}' moet {} zijn, maar krijgt {}.",
+                name.lexeme, annotation, value
+            );
+            self.error(name.line, msg);
+        }
+    }
+
+    fn check_statements(&mut self, statements: &[Stmt]) {
+        for stmt in statements {
+            self.check_stmt(stmt);
+        }
+    }
+
+    fn check_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Expr(expr)
+            | Stmt::Print(expr)
+            | Stmt::Println(expr)
+            | Stmt::Yield { expr, .. } => {
+                self.check_expr(expr);
+            }
+            Stmt::Var {
+                name,
+                annotation,
+                expr,
+                ..
+            } => {
+                let value = self.check_expr(expr);
+                let annotation = annotation.as_ref().map(|token| self.annotation(token));
+
+                let ty = match &annotation {
+                    Some(annotation) => {
+                        // 'laat x: getal;' is niks until something is assigned to it
+                        if !matches!(expr, Expr::Lit(Literal::Nil)) {
+                            self.check_fits(name, &value, annotation);
+                        }
+                        annotation.clone()
+                    }
+                    None if self.assigned.contains(&name.lexeme) => Type::Any,
+                    None => value,
+                };
+                let variable = Variable {
+                    ty,
+                    annotation,
+                    signature: None,
+                };
+                self.declare(name, variable);
+            }
+            Stmt::Destructure { pattern, expr, .. } => {
+                self.check_expr(expr);
+                for target in pattern.targets() {
+                    if let Expr::Var(name) = target {
+                        self.declare(name, Variable::any());
+                    }
+                }
+            }
+            Stmt::Block(statements) => {
+                self.begin_scope();
+                self.check_statements(statements);
+                self.end_scope();
+            }
+            // 'als' and 'terwijl' treat anything that isn't a waarheidswaarde as nietus
+            Stmt::If {
+                first_if,
+                else_ifs,
+                final_else,
+            } => {
+                for If {
+                    should_execute,
+                    statement,
+                    ..
+                } in std::iter::once(first_if).chain(else_ifs)
+                {
+                    self.check_expr(should_execute);
+                    self.check_stmt(statement);
+                }
+                if let Some(final_else) = final_else {
+                    self.check_stmt(final_else);
+                }
+            }
+            Stmt::While {
+                condition, body, ..
+            } => {
+                self.check_expr(condition);
+                self.check_stmt(body);
+            }
+            Stmt::For {
+                name,
+                start,
+                end,
+                body,
+            } => {
+                self.check_expr(start);
+                self.check_expr(end);
+                self.check_loop(name, body);
+            }
+            Stmt::ForEach {
+                name,
+                iterable,
+                body,
+            } => {
+                self.check_expr(iterable);
+                self.check_loop(name, body);
+            }
+            Stmt::Function(function) => {
+                let signature = Rc::new(self.signature(function));
+                let variable = Variable {
+                    ty: Type::Function,
+                    annotation: None,
+                    signature: self.unless_assigned(&function.name, &signature),
+                };
+                self.declare(&function.name, variable);
+                self.check_function(function, &signature);
+            }
+            Stmt::Class { name, methods } => {
+                self.classes.insert(name.lexeme.clone());
+                let signatures: Vec<Signature> = methods
+                    .iter()
+                    .map(|method| self.signature(method))
+                    .collect();
+
+                // calling the class calls 'nieuw' and gives the new instance
+                let params = methods
+                    .iter()
+                    .zip(&signatures)
+                    .find(|(method, _)| method.name.lexeme == CONSTRUCTOR)
+                    .map_or(Vec::new(), |(_, constructor)| constructor.params.clone());
+                let class = Rc::new(Signature {
+                    params,
+                    returns: Type::Instance(name.lexeme.clone()),
+                });
+                let variable = Variable {
+                    ty: Type::Function,
+                    annotation: None,
+                    signature: self.unless_assigned(name, &class),
+                };
+                self.declare(name, variable);
+
+                for (method, signature) in methods.iter().zip(&signatures) {
+                    self.check_function(method, signature);
+                }
+            }
+            Stmt::Return { keyword, expr } => {
+                let value = self.check_expr(expr);
+                let Some((name, returns)) = self.returns.last() else {
+                    return;
+                };
+                if !value.fits(returns) {
+                    let msg = format!("'{}' moet {} geven, maar geeft {}.", name, returns, value);
+                    self.error(keyword.line, msg);
+                }
+            }
+            Stmt::Test { body, .. } => {
+                self.begin_scope();
+                self.check_statements(body);
+                self.end_scope();
+            }
+        }
+    }
+
+    fn unless_assigned(&self, name: &Token, signature: &Rc<Signature>) -> Option<Rc<Signature>> {
+        if self.assigned.contains(&name.lexeme) {
+            None
+        } else {
+            Some(Rc::clone(signature))
+        }
+    }
+
+    fn check_function(&mut self, function: &FunDeclaration, signature: &Signature) {
+        self.functions.push(self.scopes.len());
+        self.begin_scope();
+        for (param, (_, ty)) in function.params.iter().zip(&signature.params) {
+            let variable = Variable {
+                ty: ty.clone(),
+                annotation: Some(ty.clone()),
+                signature: None,
+            };
+            self.declare(param, variable);
+        }
+
+        let returns = (function.name.lexeme.clone(), signature.returns.clone());
+        self.returns.push(returns);
+        self.check_statements(&function.body);
+        self.returns.pop();
+        self.end_scope();
+        self.functions.pop();
+    }
+
+    fn check_loop(&mut self, name: &Token, body: &Stmt) {
+        self.begin_scope();
+        self.declare(name, Variable::any());
+        self.check_stmt(body);
+        self.end_scope();
+    }
+
+    // returns the type of the value of the expression
+    fn check_expr(&mut self, expr: &Expr) -> Type {
+        match expr {
+            Expr::Lit(literal) => match literal {
+                Literal::Str(_) => Type::Str,
+                Literal::Int(_) | Literal::Big(_) | Literal::Num(_) => Type::Number,
+                Literal::True | Literal::False => Type::Bool,
+                Literal::Nil | Literal::None => Type::Nil,
+            },
+            Expr::Var(name) => self
+                .lookup(name)
+                .map_or(Type::Any, |variable| variable.ty.clone()),
+            Expr::This(_) => Type::Any,
+            Expr::Grouping(expr) => self.check_expr(expr),
+            Expr::Unary(op, right) => {
+                let right = self.check_expr(right);
+                self.check_unary(op, right)
+            }
+            Expr::Binary(left, op, right) => {
+                let left = self.check_expr(left);
+                let right = self.check_expr(right);
+                self.check_binary(left, op, right)
+            }
+            Expr::Logic(left, op, right) => {
+                let left = self.check_expr(left);
+                let right = self.check_expr(right);
+                self.check_logic(left, op, right)
+            }
+            Expr::Conditional {
+                keyword,
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let condition = self.check_expr(condition);
+                if !condition.fits(&Type::Bool) {
+                    let msg = format!(
+                        "De voorwaarde moet een waarheidswaarde zijn, maar is {}.",
+                        condition
+                    );
+                    self.error(keyword.line, msg);
+                }
+
+                let then_branch = self.check_expr(then_branch);
+                let else_branch = self.check_expr(else_branch);
+                if then_branch == else_branch {
+                    then_branch
+                } else {
+                    Type::Any
+                }
+            }
+            Expr::Assign(name, value) => {
+                let value = self.check_expr(value);
+                self.assigned.insert(name.lexeme.clone());
+                let annotation = self
+                    .lookup(name)
+                    .and_then(|variable| variable.annotation.clone());
+                if let Some(annotation) = annotation {
+                    self.check_fits(name, &value, &annotation);
+                }
+                // assigning gives niks, like in the interpreter
+                Type::Nil
+            }
+            Expr::AssignToElement {
+                var, index, value, ..
+            } => {
+                self.check_expr(var);
+                self.check_expr(index);
+                self.check_expr(value);
+                Type::Nil
+            }
+            // the types of the elements aren't known, so they fit any annotation
+            Expr::Destructure { pattern, value } => {
+                self.check_expr(value);
+                for target in pattern.targets() {
+                    match target {
+                        Expr::Var(name) => {
+                            self.assigned.insert(name.lexeme.clone());
+                        }
+                        target => {
+                            self.check_expr(target);
+                        }
+                    }
+                }
+                Type::Nil
+            }
+            Expr::Call(callee, paren, arguments) => {
+                let called = match callee.as_ref() {
+                    Expr::Var(name) => self
+                        .lookup(name)
+                        .and_then(|variable| variable.signature.clone())
+                        .map(|signature| (name, signature)),
+                    _ => None,
+                };
+                self.check_expr(callee);
+                let arguments: Vec<Type> = arguments
+                    .iter()
+                    .map(|argument| self.check_expr(argument))
+                    .collect();
+
+                let Some((name, signature)) = called else {
+                    return Type::Any;
+                };
+                for ((param, ty), argument) in signature.params.iter().zip(&arguments) {
+                    if !argument.fits(ty) {
+                        let msg = format!(
+                            "'{}' van '{}' moet {} zijn, maar krijgt {}.",
+                            param, name.lexeme, ty, argument
+                        );
+                        self.error(paren.line, msg);
+                    }
+                }
+                signature.returns.clone()
+            }
+            Expr::List(elements) => {
+                for element in elements {
+                    self.check_expr(element);
+                }
+                Type::List
+            }
+            Expr::Element { var, index, .. } => {
+                self.check_expr(var);
+                self.check_expr(index);
+                Type::Any
+            }
+            Expr::Slice {
+                var, start, end, ..
+            } => {
+                let var = self.check_expr(var);
+                for bound in [start, end].into_iter().flatten() {
+                    self.check_expr(bound);
+                }
+                match var {
+                    Type::List | Type::Str => var,
+                    _ => Type::Any,
+                }
+            }
+            Expr::Get { object, .. } => {
+                self.check_expr(object);
+                Type::Any
+            }
+            Expr::Set { object, value, .. } => {
+                self.check_expr(object);
+                self.check_expr(value);
+                Type::Nil
+            }
+        }
+    }
+
+    fn check_unary(&mut self, op: &Token, right: Type) -> Type {
+        let expected = match op.kind {
+            TokenType::Bang => Type::Bool,
+            _ => Type::Number,
+        };
+        if !right.fits(&expected) {
+            let msg = format!("'{}' kan niet worden gebruikt op {}.", op.lexeme, right);
+            self.error(op.line, msg);
+        }
+        expected
+    }
+
+    // only an error if the operator can't work on any of the values the operands might have
+    fn check_binary(&mut self, left: Type, op: &Token, right: Type) -> Type {
+        let mut results = Vec::new();
+        for left in left.possible() {
+            for right in right.possible() {
+                results.extend(binary_type(&left, op.kind, &right));
+            }
+        }
+
+        match results.split_first() {
+            Some((first, rest)) if rest.iter().all(|result| result == first) => first.clone(),
+            Some(_) => Type::Any,
+            None => {
+                self.operator_error(op, &left, &right);
+                Type::Any
+            }
+        }
+    }
+
+    fn check_logic(&mut self, left: Type, op: &Token, right: Type) -> Type {
+        if op.kind == TokenType::QuestionQuestion {
+            return match left {
+                Type::Nil => right,
+                Type::Any => Type::Any,
+                left => left,
+            };
+        }
+
+        if !left.fits(&Type::Bool) || !right.fits(&Type::Bool) {
+            self.operator_error(op, &left, &right);
+        }
+        Type::Bool
+    }
+
+    fn operator_error(&mut self, op: &Token, left: &Type, right: &Type) {
+        let msg = format!(
+            "'{}' kan niet worden gebruikt op {} en {}.",
+            op.lexeme, left, right
+        );
+        self.error(op.line, msg);
+    }
+}
+
+// the type that the operator gives for two values of these types, None for a runtime error
+fn binary_type(left: &Type, op: TokenType, right: &Type) -> Option<Type> {
+    use Type::*;
+
+    // an instance on the left decides for itself what the operator does
+    if let (Instance(_), Some(_)) = (left, operator_method(op)) {
+        return match op {
+            TokenType::EqualEqual | TokenType::BangEqual | TokenType::Less => Some(Bool),
+            _ => Some(Any),
+        };
+    }
+
+    match op {
+        TokenType::Plus => match (left, right) {
+            (Number, Number) => Some(Number),
+            (Number | Str, Str) | (Str, Number) => Some(Str),
+            _ => None,
+        },
+        TokenType::Minus
+        | TokenType::Star
+        | TokenType::Percent
+        | TokenType::Backslash
+        | TokenType::Slash
+        | TokenType::Caret
+        | TokenType::Ampersand
+        | TokenType::Pipe
+        | TokenType::Tilde
+        | TokenType::LessLess
+        | TokenType::GreaterGreater => (*left == Number && *right == Number).then_some(Number),
+        TokenType::Less | TokenType::LessEqual | TokenType::Greater | TokenType::GreaterEqual => {
+            (left == right && matches!(left, Number | Str | List)).then_some(Bool)
+        }
+        TokenType::EqualEqual | TokenType::BangEqual => Some(Bool),
+        _ => panic!("Unreachable."),
+    }
+}
+
+impl Variable {
+    fn any() -> Self {
+        Self {
+            ty: Type::Any,
+            annotation: None,
+            signature: None,
+        }
+    }
+}
+
+impl Type {
+    fn fits(&self, annotation: &Type) -> bool {
+        *self == Type::Any || *annotation == Type::Any || self == annotation
+    }
+
+    // the types a value of this type can have at runtime
+    fn possible(&self) -> Vec<Type> {
+        match self {
+            Type::Any => vec![
+                Type::Number,
+                Type::Str,
+                Type::Bool,
+                Type::List,
+                Type::Function,
+                Type::Nil,
+                Type::Instance(String::new()),
+            ],
+            ty => vec![ty.clone()],
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Any => write!(f, "een waarde"),
+            Type::Number => write!(f, "een getal"),
+            Type::Str => write!(f, "een reeks"),
+            Type::Bool => write!(f, "een waarheidswaarde"),
+            Type::List => write!(f, "een lijst"),
+            Type::Function => write!(f, "een proces"),
+            Type::Nil => write!(f, "niks"),
+            Type::Instance(class) => write!(f, "een {}", class),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        engine::Engine,
+        error::{Diagnostic, EvalError},
+    };
+
+    fn type_errors(source: &str) -> Vec<Diagnostic> {
+        match Engine::new().check(source) {
+            Ok(_) => Vec::new(),
+            Err(EvalError::Type(errors)) => errors,
+            Err(err) => panic!("Expected type errors, got {:?}.", err),
+        }
+    }
+
+    fn assert_errors(source: &str, expected: &[(usize, &str)]) {
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(line, message)| Diagnostic::new(*line, *message))
+            .collect();
+        assert_eq!(type_errors(source), expected, "{}", source);
+    }
+
+    #[test]
+    fn annotations() {
+        let source = "
+            laat x: getal = 3;
+            laat y: reeks = 3;
+            laat z: getal;
+            x = \"drie\";
+            klas Punt { nieuw(x: getal, y: getal) {} }
+            laat p: Punt = Punt(1, \"twee\");
+            laat q: Punt = 1;
+            laat r: Onbekend = 1;
+            proces f(a: lijst): waarheidswaarde {
+                als a == [] geef wellus;
+                geef 1;
+            }
+            f([1]);
+            f(\"lijst\");
+            f([\"lijst\"][0]);
+            laat w: waarde = f;
+            proces g(): niks { geef; }";
+        assert_errors(
+            source,
+            &[
+                (3, "'y' moet een reeks zijn, maar krijgt een getal."),
+                (5, "'x' moet een getal zijn, maar krijgt een reeks."),
+                (
+                    7,
+                    "'y' van 'Punt' moet een getal zijn, maar krijgt een reeks.",
+                ),
+                (8, "'q' moet een Punt zijn, maar krijgt een getal."),
+                (9, "'Onbekend' is geen type."),
+                (
+                    12,
+                    "'f' moet een waarheidswaarde geven, maar geeft een getal.",
+                ),
+                (
+                    15,
+                    "'a' van 'f' moet een lijst zijn, maar krijgt een reeks.",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn inference() {
+        let source = "
+            laat a = \"a\";
+            laat b = a + 1;
+            roep b - 1;
+            laat c = 1;
+            c = \"c\";
+            roep c - 1;
+            proces f(x) {
+                laat y = x;
+                roep y - 1;
+                roep -a;
+            }
+            proces g(): lijst { geef [1]; }
+            roep g() < [2];
+            roep g() < 2;
+            roep !(1 < 2) en 3;";
+        assert_errors(
+            source,
+            &[
+                (4, "'-' kan niet worden gebruikt op een reeks en een getal."),
+                (11, "'-' kan niet worden gebruikt op een reeks."),
+                (
+                    15,
+                    "'<' kan niet worden gebruikt op een lijst en een getal.",
+                ),
+                (
+                    16,
+                    "'en' kan niet worden gebruikt op een waarheidswaarde en een getal.",
+                ),
+            ],
+        );
+    }
+
+    // without annotations only operators that can never work are errors
+    #[test]
+    fn assignments_give_nil() {
+        let source = "
+            laat x = 1;
+            laat l = [1];
+            klas K {}
+            laat k = K();
+            laat a: niks = (x = 3);
+            laat b: niks = (l[0] = 3);
+            laat c: niks = (k.x = 3);
+            laat d: getal = (x = 3);
+            laat e: getal = (l[0] = 3);
+            laat f: getal = (k.x = 3);";
+        assert_errors(
+            source,
+            &[
+                (9, "'d' moet een getal zijn, maar krijgt niks."),
+                (10, "'e' moet een getal zijn, maar krijgt niks."),
+                (11, "'f' moet een getal zijn, maar krijgt niks."),
+            ],
+        );
+    }
+
+    #[test]
+    fn unknown_types() {
+        let source = "
+            proces f(x, y) { geef x - y + \"a\"; }
+            klas Geld { plus(ander) { geef 1; } }
+            roep Geld() + [];
+            roep Geld() / 2;
+            roep f(1, 2) ?? 1 + 2;
+            roep als 1 dan 2 anders 3;
+            roep [] + 1;";
+        assert_errors(
+            source,
+            &[
+                (5, "'/' kan niet worden gebruikt op een Geld en een getal."),
+                (
+                    7,
+                    "De voorwaarde moet een waarheidswaarde zijn, maar is een getal.",
+                ),
+                (8, "'+' kan niet worden gebruikt op een lijst en een getal."),
+            ],
+        );
+    }
+
+    // a function reads the variables of whoever calls it
+    #[test]
+    fn dynamic_scope() {
+        let source = "
+            laat x = \"a\";
+            proces f() { geef x - 1; }
+            proces g() { laat x = 5; geef f(); }
+            roep g();";
+        assert_errors(source, &[]);
+
+        let source = "
+            laat y = \"a\";
+            proces h() { geef y - 1; }";
+        assert_errors(
+            source,
+            &[(3, "'-' kan niet worden gebruikt op een reeks en een getal.")],
+        );
+    }
+
+    #[test]
+    fn stops_the_program() {
+        let mut engine = Engine::new();
+        let result = engine.eval("laat x = 1;\nroep \"a\" * 2;");
+        let Err(EvalError::Type(errors)) = result else {
+            panic!("Expected a type error.");
+        };
+        assert_eq!(errors[0].line, 2);
+        assert!(engine.get_global("x").is_none());
+
+        // the annotations are only checked before the program runs
+        let value = engine.eval("proces f(x: getal) { geef x; } geef f([\"a\"][0]);");
+        assert_eq!(value.unwrap().to_string(), "a");
+    }
+}
@@ -0,0 +1,190 @@
+use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
+
+use crate::{
+    callable::{Callable, FunDeclaration},
+    error::RuntimeErr,
+    interpreter::Interpreter,
+    token::Token,
+    token_type::TokenType,
+    value::Value,
+};
+
+// the method that is called on a new instance with the arguments given to the class
+pub const CONSTRUCTOR: &str = "nieuw";
+// the method that 'instance[index]' calls
+pub const INDEX_METHOD: &str = "index";
+
+// the method that an operator calls when the left side is an instance
+pub fn operator_method(op: TokenType) -> Option<&'static str> {
+    match op {
+        TokenType::Plus => Some("plus"),
+        TokenType::Minus => Some("min"),
+        TokenType::Star => Some("keer"),
+        TokenType::EqualEqual | TokenType::BangEqual => Some("gelijk"),
+        TokenType::Less => Some("kleiner"),
+        _ => None,
+    }
+}
+
+// a class declared with 'klas', calling it makes a new instance
+#[derive(Debug, Clone)]
+pub struct Class {
+    name: Token,
+    methods: Rc<HashMap<String, FunDeclaration>>,
+}
+impl Class {
+    pub fn new(name: Token, methods: Vec<FunDeclaration>) -> Self {
+        let methods = methods
+            .into_iter()
+            .map(|method| (method.name.lexeme.clone(), method))
+            .collect();
+
+        Self {
+            name,
+            methods: Rc::new(methods),
+        }
+    }
+}
+impl Callable for Class {
+    fn call(
+        &self,
+        arguments: Vec<Value>,
+        interpreter: &mut Interpreter,
+    ) -> Result<Value, RuntimeErr> {
+        let instance = Instance::new(self.clone());
+
+        if let Some(constructor) = self.methods.get(CONSTRUCTOR) {
+            let this = Value::Instance(instance.clone());
+            constructor.call_with(Some(this), arguments, interpreter)?;
+        }
+        Ok(Value::Instance(instance))
+    }
+
+    fn arity(&self) -> usize {
+        match self.methods.get(CONSTRUCTOR) {
+            Some(constructor) => constructor.arity(),
+            None => 0,
+        }
+    }
+
+    fn to_string(&self) -> String {
+        self.name.lexeme.clone()
+    }
+
+    fn line(&self) -> Option<usize> {
+        Some(self.name.line)
+    }
+}
+
+// instances are shared rather than copied, so a method can change the instance it is called on
+#[derive(Debug, Clone)]
+pub struct Instance(Rc<RefCell<InstanceData>>);
+
+#[derive(Debug)]
+struct InstanceData {
+    class: Class,
+    fields: HashMap<String, Value>,
+}
+
+impl Instance {
+    fn new(class: Class) -> Self {
+        Self(Rc::new(RefCell::new(InstanceData {
+            class,
+            fields: HashMap::new(),
+        })))
+    }
+
+    pub fn class_name(&self) -> String {
+        self.0.borrow().class.name.lexeme.clone()
+    }
+
+    // identifies the instance, copies of the same instance have the same id
+    pub fn id(&self) -> usize {
+        Rc::as_ptr(&self.0) as usize
+    }
+
+    // fields are looked up before methods
+    pub fn get(&self, name: &Token) -> Result<Value, RuntimeErr> {
+        if let Some(value) = self.0.borrow().fields.get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+
+        match self.method(&name.lexeme) {
+            Some(method) => Ok(Value::Callable(Box::new(method))),
+            None => {
+                let msg = format!(
+                    "'{}' heeft geen eigenschap '{}'.",
+                    self.class_name(),
+                    name.lexeme
+                );
+                Err(RuntimeErr::Err(name.line, msg))
+            }
+        }
+    }
+
+    pub fn set(&self, name: &Token, value: Value) {
+        self.0
+            .borrow_mut()
+            .fields
+            .insert(name.lexeme.clone(), value);
+    }
+
+    // changes a field in place, like an element of a list in it. Anything else that 'get'
+    // finds is a temporary value
+    pub fn update<T>(
+        &self,
+        name: &Token,
+        update: impl FnOnce(&mut Value) -> Result<T, RuntimeErr>,
+    ) -> Result<T, RuntimeErr> {
+        if let Some(field) = self.0.borrow_mut().fields.get_mut(&name.lexeme) {
+            return update(field);
+        }
+        update(&mut self.get(name)?)
+    }
+
+    pub fn method(&self, name: &str) -> Option<BoundMethod> {
+        let method = self.0.borrow().class.methods.get(name)?.clone();
+        Some(BoundMethod {
+            instance: self.clone(),
+            method,
+        })
+    }
+}
+impl Display for Instance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<instantie van {}>", self.class_name())
+    }
+}
+
+// a method together with the instance it was taken from
+#[derive(Debug, Clone)]
+pub struct BoundMethod {
+    instance: Instance,
+    method: FunDeclaration,
+}
+impl Callable for BoundMethod {
+    fn call(
+        &self,
+        arguments: Vec<Value>,
+        interpreter: &mut Interpreter,
+    ) -> Result<Value, RuntimeErr> {
+        let this = Value::Instance(self.instance.clone());
+        self.method.call_with(Some(this), arguments, interpreter)
+    }
+
+    fn arity(&self) -> usize {
+        self.method.arity()
+    }
+
+    fn to_string(&self) -> String {
+        self.method.to_string()
+    }
+
+    fn line(&self) -> Option<usize> {
+        self.method.line()
+    }
+
+    fn instance_id(&self) -> Option<usize> {
+        Some(self.instance.id())
+    }
+}
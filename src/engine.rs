@@ -0,0 +1,258 @@
+use std::io::Write;
+
+use crate::{
+    checker::Checker,
+    error::{Diagnostic, EvalError},
+    interpreter::Interpreter,
+    limits::Limits,
+    linter::Linter,
+    native::{NativeFn, NativeFunction},
+    parser::Parser,
+    permissions::Permissions,
+    resolver::Resolver,
+    scanner::Scanner,
+    stmt::Stmt,
+    value::Value,
+};
+
+const PRINT_SCAN_OUTPUT: bool = false;
+
+// a debug build uses up to 50 KB of stack for a call that loops, see 'Engine'
+const MAX_CALL_DEPTH: usize = 32;
+
+// the interpreter as a host program sees it: source code goes in, values and
+// diagnostics come out, nothing is printed except by 'zeg' and 'roep'.
+//
+// Globals survive from one 'eval' to the next, so a host can load a script once
+// and call into it later. Every Rox call nests deeper on the Rust stack, so the
+// default depth is low enough for the 2 MB a Rust thread gets, also in a debug
+// build. A host that allows deeper recursion should run the engine on a thread
+// with STACK_SIZE of stack and raise it with 'set_max_call_depth', like 'rox' does
+pub struct Engine {
+    pub(crate) interpreter: Interpreter,
+    // globals that aren't one of these were declared by a program or the host
+    builtins: Vec<String>,
+    warnings: Vec<Diagnostic>,
+}
+impl Engine {
+    // a program can only use the builtins that compute things, see 'with_permissions'
+    pub fn new() -> Self {
+        Self::with_permissions(&Permissions::pure())
+    }
+
+    pub fn with_permissions(permissions: &Permissions) -> Self {
+        let mut interpreter = Interpreter::new(permissions);
+        interpreter.max_call_depth = MAX_CALL_DEPTH;
+        let builtins = interpreter.env.global_names();
+
+        Self {
+            interpreter,
+            builtins,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.interpreter.max_call_depth = depth;
+    }
+
+    // the limits apply to every 'eval' on its own
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.interpreter.limits = limits;
+    }
+
+    // where 'zeg' and 'roep' write to instead of stdout, see 'Buffer'
+    pub fn set_output(&mut self, output: impl Write + 'static) {
+        self.interpreter.output = Box::new(output);
+    }
+
+    // runs the source and returns the value of a 'geef' outside of any function,
+    // or niks if there is none. Stops at the first error
+    pub fn eval(&mut self, source: &str) -> Result<Value, EvalError> {
+        let statements = self.compile(source)?;
+        let result = self.interpreter.run(&statements);
+        let _ = self.interpreter.output.flush();
+        result
+    }
+
+    // the warnings of the last 'eval'
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    // compiles the source without running it, like 'rox check'. Returns the warnings
+    // of the resolver together with those of the linter, ordered by line
+    pub fn check(&mut self, source: &str) -> Result<Vec<Diagnostic>, EvalError> {
+        let statements = self.compile(source)?;
+
+        let env = &self.interpreter.env;
+        let globals = env
+            .global_names()
+            .into_iter()
+            .map(|name| {
+                let arity = match env.get_global_value(&name) {
+                    Some(Value::Callable(callable)) => Some(callable.arity()),
+                    _ => None,
+                };
+                (name, arity)
+            })
+            .collect();
+
+        let mut warnings = self.warnings.clone();
+        warnings.extend(Linter::new(globals).lint(&statements));
+        warnings.sort_by_key(|warning| warning.line);
+        Ok(warnings)
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.interpreter.env.get_global_value(name)
+    }
+
+    // declares the global if it doesn't exist yet, later programs can use it like any other
+    pub fn set_global(&mut self, name: &str, value: Value) {
+        self.interpreter
+            .env
+            .insert_global_value(name.to_string(), value);
+    }
+
+    // makes a Rust closure callable from Rox under the name. The arguments are
+    // converted with FromRox and checked before the closure is called, the
+    // return value is converted with IntoRox. A closure that returns an Err
+    // gives a runtime error with that message
+    pub fn register_fn<Args>(&mut self, name: &str, function: impl NativeFn<Args>) {
+        let function = NativeFunction::new(name, function);
+        self.set_global(name, Value::Callable(Box::new(function)));
+    }
+
+    pub(crate) fn compile(&mut self, source: &str) -> Result<Vec<Stmt>, EvalError> {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens().map_err(EvalError::Scan)?;
+
+        if PRINT_SCAN_OUTPUT {
+            for token in &tokens {
+                print!("{}_", token);
+            }
+            println!();
+        }
+
+        let mut parser = Parser::new(tokens);
+        let statements = parser.parse_statements().map_err(EvalError::Parse)?;
+
+        let globals: Vec<String> = self
+            .interpreter
+            .env
+            .global_names()
+            .into_iter()
+            .filter(|name| !self.builtins.contains(name))
+            .collect();
+
+        let mut resolver = Resolver::new(self.builtins.clone());
+        for name in &globals {
+            let constant = self.interpreter.env.is_global_constant(name);
+            resolver.declare_global(name.clone(), constant);
+        }
+        for (name, capability) in &self.interpreter.forbidden {
+            resolver.forbid(name.clone(), *capability);
+        }
+
+        let result = resolver.resolve(&statements);
+        self.warnings = std::mem::take(&mut resolver.warnings);
+        result.map_err(EvalError::Resolve)?;
+
+        Checker::new(globals)
+            .check(&statements)
+            .map_err(EvalError::Type)?;
+
+        Ok(statements)
+    }
+}
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globals_survive_between_evals() {
+        let mut engine = Engine::new();
+        engine.set_global("basis", Value::Int(40));
+
+        let value = engine.eval("proces plusTwee(n) { geef n + 2; }").unwrap();
+        assert!(matches!(value, Value::Nil));
+        let value = engine.eval("laat x = plusTwee(basis); geef x;").unwrap();
+        assert_eq!(value.to_string(), "42");
+
+        assert_eq!(engine.get_global("x").unwrap().to_string(), "42");
+        assert!(engine.get_global("y").is_none());
+
+        engine.eval("vast nul = 0;").unwrap();
+        assert!(matches!(
+            engine.eval("nul = 1;"),
+            Err(EvalError::Resolve(_))
+        ));
+    }
+
+    #[test]
+    fn errors_are_returned() {
+        let mut engine = Engine::new();
+
+        let Err(EvalError::Scan(errors)) = engine.eval("laat x = 1 ? 2;\n\"open") else {
+            panic!("Expected scan errors.");
+        };
+        assert_eq!(
+            errors,
+            [
+                Diagnostic::new(1, "Verwachtte '??'."),
+                Diagnostic::new(2, "Ongetermineerde reeks."),
+            ]
+        );
+
+        let Err(EvalError::Parse(errors)) = engine.eval("laat = 1;") else {
+            panic!("Expected a parse error.");
+        };
+        assert_eq!(errors[0].line, 1);
+
+        let Err(EvalError::Resolve(errors)) = engine.eval("roep onbekend;") else {
+            panic!("Expected a resolve error.");
+        };
+        assert_eq!(errors[0].message, "'onbekend' is een onbekende variabele.");
+
+        // the program stops at the first runtime error
+        let source = "laat a = 1;\nlaat b = a();\nlaat c = 3;";
+        let Err(EvalError::Runtime(error)) = engine.eval(source) else {
+            panic!("Expected a runtime error.");
+        };
+        assert_eq!(error.line, 2);
+        assert!(engine.get_global("a").is_some());
+        assert!(engine.get_global("c").is_none());
+    }
+
+    // recursion stops with an error before it overflows the stack of a thread that
+    // isn't given more than the default
+    #[test]
+    fn recursion_fits_a_default_thread() {
+        let message = std::thread::spawn(|| {
+            let source = "proces f(n) { als n == 0 geef 0; geef 1 + f(n - 1); } geef f(999);";
+            match Engine::new().eval(source) {
+                Err(EvalError::Runtime(error)) => error.message,
+                _ => panic!("Expected a runtime error."),
+            }
+        })
+        .join()
+        .unwrap();
+        assert_eq!(message, "Maximale recursiediepte van 32 bereikt.");
+    }
+
+    #[test]
+    fn warnings() {
+        let mut engine = Engine::new();
+        engine.eval("laat lengte = 3;").unwrap();
+        assert_eq!(engine.warnings().len(), 1);
+        engine.eval("laat x = lengte;").unwrap();
+        assert!(engine.warnings().is_empty());
+    }
+}
@@ -1,16 +1,19 @@
 use crate::{error::RuntimeErr, token::Token, value::Value};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone)]
 pub struct Env {
     child: Option<Box<Env>>,
     vars: HashMap<String, Value>,
+    // names declared with 'vast' in this scope
+    constants: HashSet<String>,
 }
 impl Env {
     pub fn new() -> Self {
         Self {
             child: None,
             vars: HashMap::new(),
+            constants: HashSet::new(),
         }
     }
 
@@ -20,9 +23,8 @@ impl Env {
                 println!("{}: {:?}", i, value);
             }
         }
-        match &self.child {
-            Some(child) => child.print_children(i + 1),
-            None => return,
+        if let Some(child) = &self.child {
+            child.print_children(i + 1);
         }
     }
 
@@ -47,66 +49,150 @@ impl Env {
         }
     }
 
+    // attaches a scope that was taken out with 'take_youngest_child' again
+    pub fn push_child(&mut self, env: Env) {
+        match &mut self.child {
+            Some(child) => child.push_child(env),
+            None => self.child = Some(Box::new(env)),
+        }
+    }
+
+    // like 'kill_youngest_child', but hands the scope over instead of dropping it
+    pub fn take_youngest_child(&mut self) -> Option<Env> {
+        match &mut self.child {
+            Some(child) if child.child.is_some() => child.take_youngest_child(),
+            _ => self.child.take().map(|child| *child),
+        }
+    }
+
+    pub fn global_names(&self) -> Vec<String> {
+        self.vars.keys().cloned().collect()
+    }
+
+    pub fn get_global_value(&self, name: &str) -> Option<Value> {
+        self.vars.get(name).cloned()
+    }
+
+    pub fn is_global_constant(&self, name: &str) -> bool {
+        self.constants.contains(name)
+    }
+
     pub fn insert_global_value(&mut self, name: String, value: Value) {
         self.vars.insert(name, value);
     }
 
     pub fn insert_value(&mut self, name: &String, value: Value) {
         match self.child {
-            Some(ref mut child) => child.insert_value(&name, value),
+            Some(ref mut child) => child.insert_value(name, value),
             None => {
+                self.constants.remove(name);
+                self.vars.insert(name.clone(), value);
+            }
+        }
+    }
+
+    pub fn insert_constant(&mut self, name: &String, value: Value) {
+        match self.child {
+            Some(ref mut child) => child.insert_constant(name, value),
+            None => {
+                self.constants.insert(name.clone());
                 self.vars.insert(name.clone(), value);
             }
         }
     }
 
     pub fn get_value(&self, token: &Token) -> Option<Value> {
+        self.value(token).cloned()
+    }
+
+    // the value of a variable without copying it
+    pub fn value(&self, token: &Token) -> Option<&Value> {
         if let Some(child) = &self.child {
-            if let Some(value) = child.get_value(token) {
+            if let Some(value) = child.value(token) {
                 return Some(value);
             }
         }
-        self.vars.get(&token.lexeme).cloned()
+        self.vars.get(&token.lexeme)
     }
 
-    pub fn replace_element(
-        &mut self,
-        name: &Token,
-        index: usize,
-        new_value: &Value,
-    ) -> Result<(), RuntimeErr> {
-        if let Some(ref mut child) = self.child {
-            if let Ok(()) = child.replace_element(name, index, new_value) {
-                return Ok(());
+    // the value of a variable to change in place, like an element of a list in it.
+    // Lists bound with 'vast' can't be changed either
+    pub fn value_mut(&mut self, name: &Token) -> Result<&mut Value, RuntimeErr> {
+        if !self.declares(&name.lexeme) {
+            let msg = format!("'{}' is een onbekende variabele.", name.lexeme);
+            return Err(RuntimeErr::Err(name.line, msg));
+        }
+        self.declared_value_mut(name)
+    }
+
+    // instances are shared, so writing to a property of one that is reached through a
+    // constant would change the constant
+    pub fn check_not_constant(&self, name: &Token) -> Result<(), RuntimeErr> {
+        if let Some(child) = &self.child {
+            if child.declares(&name.lexeme) {
+                return child.check_not_constant(name);
             }
         }
 
-        if let Some(old_value) = self.vars.get_mut(&name.lexeme) {
-            if let Value::List(elements) = old_value {
-                elements[index] = new_value.clone();
-                Ok(())
-            } else {
-                let msg = format!("'{}' is geen lijst.", name.lexeme);
-                Err(RuntimeErr::Err(name.line, msg))
+        if self.constants.contains(&name.lexeme) {
+            return Err(constant_err(name));
+        }
+        Ok(())
+    }
+
+    fn declares(&self, name: &str) -> bool {
+        self.vars.contains_key(name) || self.child.as_ref().is_some_and(|c| c.declares(name))
+    }
+
+    // the innermost scope that declares the name wins
+    fn declared_value_mut(&mut self, name: &Token) -> Result<&mut Value, RuntimeErr> {
+        if let Some(child) = &mut self.child {
+            if child.declares(&name.lexeme) {
+                return child.declared_value_mut(name);
             }
+        }
+
+        if self.constants.contains(&name.lexeme) {
+            return Err(constant_err(name));
+        }
+        Ok(self.vars.get_mut(&name.lexeme).unwrap())
+    }
+
+    pub fn replace_value(&mut self, name: &Token, new_value: &Value) -> Result<(), RuntimeErr> {
+        if self.try_replace_value(name, new_value)? {
+            Ok(())
         } else {
             let msg = format!("'{}' is een onbekende variabele.", name.lexeme);
             Err(RuntimeErr::Err(name.line, msg))
         }
     }
 
-    pub fn replace_value(&mut self, name: &Token, new_value: &Value) -> Result<(), RuntimeErr> {
+    // returns false if the variable is not declared in this scope or its children
+    fn try_replace_value(&mut self, name: &Token, new_value: &Value) -> Result<bool, RuntimeErr> {
         if let Some(ref mut child) = self.child {
-            if let Ok(()) = child.replace_value(name, new_value) {
-                return Ok(());
+            if child.try_replace_value(name, new_value)? {
+                return Ok(true);
             }
         }
-        if let Some(old_value) = self.vars.get_mut(&name.lexeme) {
-            *old_value = new_value.clone();
-            Ok(())
-        } else {
-            let msg = format!("'{}' is een onbekende variabele.", name.lexeme);
-            Err(RuntimeErr::Err(name.line, msg))
+
+        if self.constants.contains(&name.lexeme) {
+            return Err(constant_err(name));
+        }
+
+        match self.vars.get_mut(&name.lexeme) {
+            Some(old_value) => {
+                *old_value = new_value.clone();
+                Ok(true)
+            }
+            None => Ok(false),
         }
     }
 }
+
+fn constant_err(name: &Token) -> RuntimeErr {
+    let msg = format!(
+        "'{}' is een constante en kan niet worden aangepast.",
+        name.lexeme
+    );
+    RuntimeErr::Err(name.line, msg)
+}
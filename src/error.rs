@@ -1,27 +1,87 @@
+use std::{fmt::Display, io::Write};
+
 use crate::value::Value;
 use colored::Colorize;
 
-pub fn rox_error(line: usize, message: &str) {
+// diagnostics that can't be written anywhere are dropped, there's no better place for them
+pub fn rox_error(out: &mut dyn Write, line: usize, message: &str) {
     let l = "[line ".blue();
     let i = "] Error: ".blue();
     let message = message.red();
-    println!("{}{}{}{}", l, line, i, message);
+    let _ = writeln!(out, "{}{}{}{}", l, line, i, message);
 }
 
-pub fn crash(line: usize, message: &str) -> ! {
+pub fn rox_warning(out: &mut dyn Write, line: usize, message: &str) {
     let l = "[line ".blue();
-    let i = "] Error: ".blue();
-    let message = message.red();
-    panic!("{}{}{}{}", l, line, i, message);
+    let i = "] Waarschuwing: ".blue();
+    let message = message.yellow();
+    let _ = writeln!(out, "{}{}{}{}", l, line, i, message);
 }
 
 #[derive(Debug, Clone)]
 pub enum RoxError {
-    ScanError,
     ParseError { line: usize, msg: String },
 }
 
+// an error or warning and the line it was found on. The stages collect these
+// instead of printing them, so a host program can decide what to do with them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub message: String,
+}
+impl Diagnostic {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[line {}] {}", self.line, self.message)
+    }
+}
+
+// why evaluating source code failed, by the stage that found the problem
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum EvalError {
+    Scan(Vec<Diagnostic>),
+    Parse(Vec<Diagnostic>),
+    Resolve(Vec<Diagnostic>),
+    Type(Vec<Diagnostic>),
+    Runtime(Diagnostic),
+    // the program went over one of its limits
+    Limit(Diagnostic),
+}
+impl EvalError {
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        match self {
+            EvalError::Scan(diagnostics)
+            | EvalError::Parse(diagnostics)
+            | EvalError::Resolve(diagnostics)
+            | EvalError::Type(diagnostics) => diagnostics,
+            EvalError::Runtime(diagnostic) | EvalError::Limit(diagnostic) => {
+                std::slice::from_ref(diagnostic)
+            }
+        }
+    }
+}
+impl Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let diagnostics: Vec<String> = self.diagnostics().iter().map(|d| d.to_string()).collect();
+        write!(f, "{}", diagnostics.join("\n"))
+    }
+}
+impl std::error::Error for EvalError {}
+
 pub enum RuntimeErr {
     Err(usize, String),
+    // a limit from 'Limits' was reached, this stops the whole program
+    Limit(usize, String),
     Return { value: Value },
+    // 'geef f(...)' inside f itself, f starts over with the new arguments instead of recursing
+    TailCall { arguments: Vec<Value> },
 }
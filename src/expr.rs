@@ -1,17 +1,26 @@
+use std::fmt::Display;
+
 use crate::token::{Literal, Token};
 
 #[derive(Clone, Debug)]
 pub enum Expr {
     Lit(Literal),
     Logic(Box<Expr>, Token, Box<Expr>),
+    Conditional {
+        keyword: Token,
+        condition: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>,
+    },
     Grouping(Box<Expr>),
     Unary(Token, Box<Expr>),
     Binary(Box<Expr>, Token, Box<Expr>),
     Var(Token),
-    AssignToExpr(Token, Box<Expr>),
+    Assign(Token, Box<Expr>),
     AssignToElement {
         var: Box<Expr>,
         index: Box<Expr>,
+        right_bracket: Token,
         value: Box<Expr>,
     },
     Call(Box<Expr>, Token, Vec<Expr>),
@@ -21,11 +30,97 @@ pub enum Expr {
         index: Box<Expr>,
         right_bracket: Token,
     },
+    // lijst[start:end], both bounds are optional
+    Slice {
+        var: Box<Expr>,
+        start: Option<Box<Expr>>,
+        end: Option<Box<Expr>>,
+        right_bracket: Token,
+    },
+    Destructure {
+        pattern: Box<Pattern>,
+        value: Box<Expr>,
+    },
+    // object.name
+    Get {
+        object: Box<Expr>,
+        name: Token,
+    },
+    Set {
+        object: Box<Expr>,
+        name: Token,
+        value: Box<Expr>,
+    },
+    This(Token),
 }
-// used for debugging purposes
+
 impl Expr {
-    pub fn to_string(&self) -> String {
+    // the line of the first token in the expression, literals don't keep their token
+    pub fn line(&self) -> Option<usize> {
         match self {
+            Expr::Lit(_) => None,
+            Expr::Grouping(expr) => expr.line(),
+            Expr::List(elements) => elements.iter().find_map(Expr::line),
+            Expr::Destructure { value, .. } => value.line(),
+            Expr::Logic(_, token, _)
+            | Expr::Conditional { keyword: token, .. }
+            | Expr::Unary(token, _)
+            | Expr::Binary(_, token, _)
+            | Expr::Var(token)
+            | Expr::Assign(token, _)
+            | Expr::AssignToElement {
+                right_bracket: token,
+                ..
+            }
+            | Expr::Call(_, token, _)
+            | Expr::Element {
+                right_bracket: token,
+                ..
+            }
+            | Expr::Slice {
+                right_bracket: token,
+                ..
+            }
+            | Expr::Get { name: token, .. }
+            | Expr::Set { name: token, .. }
+            | Expr::This(token) => Some(token.line),
+        }
+    }
+}
+
+// the left side of a destructuring declaration or assignment, e.g. '[a, [b, c]]' or 'a, b'
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    // a variable, or for assignments also a list element or a property
+    Target(Expr),
+    List {
+        elements: Vec<Pattern>,
+        token: Token,
+    },
+}
+impl Pattern {
+    pub fn targets(&self) -> Vec<&Expr> {
+        match self {
+            Pattern::Target(target) => vec![target],
+            Pattern::List { elements, .. } => elements.iter().flat_map(Pattern::targets).collect(),
+        }
+    }
+}
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Target(target) => write!(f, "{}", target),
+            Pattern::List { elements, .. } => {
+                let elements: Vec<String> = elements.iter().map(|e| e.to_string()).collect();
+                write!(f, "[{}]", elements.join(" "))
+            }
+        }
+    }
+}
+// prints the expression as an s-expression, used for debugging and for testing the parser
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let out = match self {
             Expr::Lit(lit) => lit.to_string(),
             Expr::Grouping(expr) => {
                 let expr = *expr.clone();
@@ -40,32 +135,76 @@ impl Expr {
                 let right = *right.clone();
                 parenthesize(token.lexeme.clone(), vec![left, right])
             }
-            Expr::AssignToExpr(_, _) => panic!("Unreachable."),
-            Expr::Var(_) => panic!("Unreachable."),
-            Expr::Call(_, _, _) => panic!("Unreachable."),
-            Expr::List(_) => panic!("Unreachable."),
-            Expr::Element {
-                right_bracket: _,
-                var: _,
-                index: _,
-            } => panic!("Unreachable."),
+            Expr::Conditional {
+                keyword,
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let exprs = vec![
+                    *condition.clone(),
+                    *then_branch.clone(),
+                    *else_branch.clone(),
+                ];
+                parenthesize(keyword.lexeme.clone(), exprs)
+            }
+            Expr::Var(name) => name.lexeme.clone(),
+            Expr::Assign(name, value) => {
+                let value = *value.clone();
+                parenthesize(format!("= {}", name.lexeme), vec![value])
+            }
+            Expr::Call(callee, _, arguments) => {
+                let mut exprs = vec![*callee.clone()];
+                exprs.extend(arguments.clone());
+                parenthesize("call".to_owned(), exprs)
+            }
+            Expr::List(elements) => parenthesize("list".to_owned(), elements.clone()),
+            Expr::Element { var, index, .. } => {
+                parenthesize("index".to_owned(), vec![*var.clone(), *index.clone()])
+            }
+            Expr::Destructure { pattern, value } => {
+                parenthesize(format!("= {}", pattern), vec![*value.clone()])
+            }
+            Expr::Slice {
+                var, start, end, ..
+            } => {
+                let bound = |bound: &Option<Box<Expr>>| match bound {
+                    Some(bound) => bound.to_string(),
+                    None => "_".to_string(),
+                };
+                format!("(slice {} {} {})", var, bound(start), bound(end))
+            }
+            Expr::Get { object, name } => {
+                parenthesize(format!(". {}", name.lexeme), vec![*object.clone()])
+            }
+            Expr::Set {
+                object,
+                name,
+                value,
+            } => {
+                let property = parenthesize(format!(". {}", name.lexeme), vec![*object.clone()]);
+                parenthesize(format!("= {}", property), vec![*value.clone()])
+            }
+            Expr::This(keyword) => keyword.lexeme.clone(),
             Expr::AssignToElement {
-                var: _,
-                value: _,
-                index: _,
-            } => panic!("Unreachable."),
-        }
+                var, index, value, ..
+            } => {
+                let element = parenthesize("index".to_owned(), vec![*var.clone(), *index.clone()]);
+                parenthesize(format!("= {}", element), vec![*value.clone()])
+            }
+        };
+        write!(f, "{}", out)
     }
 }
 
 fn parenthesize(name: String, exprs: Vec<Expr>) -> String {
-    let mut out = format!("({}", name.clone());
+    let mut out = format!("({}", name);
 
-    for i in 0..exprs.len() {
-        out.push_str(" ");
-        out.push_str(&exprs[i].to_string());
+    for expr in exprs {
+        out.push(' ');
+        out.push_str(&expr.to_string());
     }
 
-    out.push_str(")");
+    out.push(')');
     out
 }
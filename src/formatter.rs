@@ -0,0 +1,754 @@
+use crate::{
+    callable::FunDeclaration,
+    error::Diagnostic,
+    expr::{Expr, Pattern},
+    parser::Parser,
+    scanner::Scanner,
+    stmt::{If, Stmt},
+    token::{Comment, Literal, Token},
+    token_type::TokenType,
+};
+
+const INDENT: &str = "    ";
+
+// formats Rox code the way 'rox fmt' does: one statement per line, four spaces of
+// indentation, '{' on the line of the statement it belongs to and spaces around
+// operators. Comments and single blank lines between statements are kept
+pub fn format(source: &str) -> Result<String, Vec<Diagnostic>> {
+    let mut scanner = Scanner::new(source.to_string());
+    let tokens = scanner.scan_tokens()?;
+    let statements = Parser::new(tokens.clone()).parse_statements()?;
+
+    let mut formatter = Formatter {
+        tokens,
+        comments: scanner.comments(),
+        current: 0,
+        next_comment: 0,
+        out: String::new(),
+        indent: 0,
+        line_start: true,
+        last_line: 0,
+        block_start: true,
+        continued: false,
+    };
+    for stmt in &statements {
+        formatter.statement(stmt);
+    }
+    formatter.leading_comments();
+    Ok(formatter.out)
+}
+
+// walks the statements together with the tokens they were parsed from. Every token is
+// written as it was in the source, only the whitespace between them changes, and the
+// lines of the tokens tell where the comments and blank lines go
+struct Formatter {
+    tokens: Vec<Token>,
+    comments: Vec<Comment>,
+    // the next token to write
+    current: usize,
+    next_comment: usize,
+    out: String,
+    indent: usize,
+    // whether nothing is written on the current line yet
+    line_start: bool,
+    // the source line of what was written last
+    last_line: usize,
+    // no blank line after a '{' or at the start of the file
+    block_start: bool,
+    // whether a comment broke the current statement over lines
+    continued: bool,
+}
+impl Formatter {
+    // a statement on its own line, after the comments that come before it
+    fn statement(&mut self, stmt: &Stmt) {
+        self.line(|f| f.stmt(stmt));
+    }
+
+    fn line(&mut self, write: impl FnOnce(&mut Self)) {
+        self.leading_comments();
+        self.blank_line(start_line(&self.tokens[self.current]));
+        write(self);
+        self.end_line();
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Expr(expr) => self.expr(expr),
+            Stmt::Print(expr) => {
+                self.keyword(TokenType::Print);
+                self.expr(expr);
+            }
+            Stmt::Println(expr) => {
+                self.keyword(TokenType::Println);
+                self.expr(expr);
+            }
+            Stmt::Var {
+                annotation,
+                expr,
+                constant,
+                ..
+            } => {
+                self.keyword(declaration_keyword(*constant));
+                self.token(TokenType::Identifier);
+                self.annotation(annotation);
+                // 'laat x;' leaves out its value niks
+                if self.check(TokenType::Equal) {
+                    self.operator(TokenType::Equal);
+                    self.expr(expr);
+                }
+            }
+            Stmt::Destructure {
+                pattern,
+                expr,
+                constant,
+            } => {
+                self.keyword(declaration_keyword(*constant));
+                self.pattern(pattern);
+                self.operator(TokenType::Equal);
+                self.expr(expr);
+            }
+            Stmt::Block(statements) => return self.block(statements),
+            Stmt::If {
+                first_if,
+                else_ifs,
+                final_else,
+            } => return self.if_stmt(first_if, else_ifs, final_else.as_deref()),
+            Stmt::While {
+                condition, body, ..
+            } => {
+                self.keyword(TokenType::While);
+                self.expr(condition);
+                return self.body(body);
+            }
+            Stmt::For {
+                start, end, body, ..
+            } => {
+                self.keyword(TokenType::For);
+                self.token(TokenType::Identifier);
+                self.operator(TokenType::From);
+                self.expr(start);
+                self.operator(TokenType::Until);
+                self.expr(end);
+                return self.body(body);
+            }
+            Stmt::ForEach { iterable, body, .. } => {
+                self.keyword(TokenType::For);
+                self.token(TokenType::Identifier);
+                self.operator(TokenType::In);
+                self.expr(iterable);
+                return self.body(body);
+            }
+            Stmt::Function(function) => {
+                self.keyword(TokenType::Fun);
+                return self.function(function);
+            }
+            Stmt::Class { methods, .. } => return self.class(methods),
+            Stmt::Return { expr, .. } => {
+                self.token(TokenType::Return);
+                // 'geef;' leaves out its value niks
+                if !self.check(TokenType::Semicolon) {
+                    self.space();
+                    self.expr(expr);
+                }
+            }
+            Stmt::Yield { expr, .. } => {
+                self.keyword(TokenType::Yield);
+                self.expr(expr);
+            }
+            Stmt::Test { body, .. } => {
+                self.keyword(TokenType::Test);
+                self.token(TokenType::String);
+                self.space();
+                return self.block(body);
+            }
+        }
+        self.token(TokenType::Semicolon);
+    }
+
+    // als a { ... } anders als b zeg 1;
+    // anders { ... }
+    fn if_stmt(&mut self, first_if: &If, else_ifs: &[If], final_else: Option<&Stmt>) {
+        self.keyword(TokenType::If);
+        self.expr(&first_if.should_execute);
+        self.body(&first_if.statement);
+
+        let mut previous = &first_if.statement;
+        for else_if in else_ifs {
+            self.before_else(previous);
+            self.keyword(TokenType::Else);
+            self.keyword(TokenType::If);
+            self.expr(&else_if.should_execute);
+            self.body(&else_if.statement);
+            previous = &else_if.statement;
+        }
+
+        if let Some(final_else) = final_else {
+            self.before_else(previous);
+            self.token(TokenType::Else);
+            self.body(final_else);
+        }
+    }
+
+    // 'anders' goes after the '}' of a block, or on the next line after a statement or a
+    // comment
+    fn before_else(&mut self, previous: &Stmt) {
+        if let (Stmt::Block(_), false) = (previous, self.comment_before(self.current + 1)) {
+            self.space();
+        } else {
+            self.end_line();
+            self.leading_comments();
+        }
+    }
+
+    // the body of 'als', 'terwijl' and 'voor' goes on the same line
+    fn body(&mut self, body: &Stmt) {
+        self.space();
+        self.stmt(body);
+    }
+
+    fn block(&mut self, statements: &[Stmt]) {
+        // a '{' that a comment put on a line of its own lines up with its '}'
+        if !self.line_start {
+            self.inner_comments();
+        }
+        self.continued = false;
+        self.token(TokenType::LeftBrace);
+        if statements.is_empty() && !self.comment_before(self.current + 1) {
+            self.token(TokenType::RightBrace);
+            return;
+        }
+
+        self.end_line();
+        self.indent += 1;
+        self.block_start = true;
+        for stmt in statements {
+            self.statement(stmt);
+        }
+        // comments after the last statement stay inside the block
+        self.leading_comments();
+        self.indent -= 1;
+        self.token(TokenType::RightBrace);
+    }
+
+    fn function(&mut self, function: &FunDeclaration) {
+        self.token(TokenType::Identifier);
+        self.token(TokenType::LeftParen);
+        self.separated(&function.param_types, |f, annotation| {
+            f.token(TokenType::Identifier);
+            f.annotation(annotation);
+        });
+        self.token(TokenType::RightParen);
+        self.annotation(&function.return_type);
+        self.space();
+        self.block(&function.body);
+    }
+
+    // x: getal
+    fn annotation(&mut self, annotation: &Option<Token>) {
+        if let Some(annotation) = annotation {
+            self.token(TokenType::Colon);
+            self.space();
+            self.token(annotation.kind);
+        }
+    }
+
+    fn class(&mut self, methods: &[FunDeclaration]) {
+        self.keyword(TokenType::Class);
+        self.token(TokenType::Identifier);
+        self.space();
+        self.token(TokenType::LeftBrace);
+        if methods.is_empty() && !self.comment_before(self.current + 1) {
+            self.token(TokenType::RightBrace);
+            return;
+        }
+
+        self.end_line();
+        self.indent += 1;
+        self.block_start = true;
+        for method in methods {
+            self.line(|f| f.function(method));
+        }
+        self.leading_comments();
+        self.indent -= 1;
+        self.token(TokenType::RightBrace);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Lit(literal) => {
+                let kind = match literal {
+                    Literal::Str(_) => TokenType::String,
+                    Literal::Int(_) | Literal::Big(_) | Literal::Num(_) => TokenType::Number,
+                    Literal::True => TokenType::True,
+                    Literal::False => TokenType::False,
+                    Literal::Nil => TokenType::Nil,
+                    Literal::None => panic!("Unreachable."),
+                };
+                self.token(kind);
+            }
+            Expr::Logic(left, op, right) | Expr::Binary(left, op, right) => {
+                self.expr(left);
+                self.operator(op.kind);
+                self.expr(right);
+            }
+            Expr::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.keyword(TokenType::If);
+                self.expr(condition);
+                self.operator(TokenType::Then);
+                self.expr(then_branch);
+                self.operator(TokenType::Else);
+                self.expr(else_branch);
+            }
+            Expr::Grouping(expr) => {
+                self.token(TokenType::LeftParen);
+                self.expr(expr);
+                self.token(TokenType::RightParen);
+            }
+            Expr::Unary(op, right) => {
+                self.token(op.kind);
+                self.expr(right);
+            }
+            Expr::Var(_) => self.token(TokenType::Identifier),
+            Expr::This(_) => self.token(TokenType::This),
+            Expr::Assign(_, value) => {
+                self.token(TokenType::Identifier);
+                self.operator(TokenType::Equal);
+                self.expr(value);
+            }
+            Expr::AssignToElement {
+                var, index, value, ..
+            } => {
+                self.element(var, index);
+                self.operator(TokenType::Equal);
+                self.expr(value);
+            }
+            Expr::Call(callee, _, arguments) => {
+                self.expr(callee);
+                self.token(TokenType::LeftParen);
+                self.separated(arguments, Self::expr);
+                self.token(TokenType::RightParen);
+            }
+            Expr::List(elements) => {
+                self.token(TokenType::LeftBracket);
+                self.separated(elements, Self::expr);
+                self.token(TokenType::RightBracket);
+            }
+            Expr::Element { var, index, .. } => self.element(var, index),
+            Expr::Slice {
+                var, start, end, ..
+            } => {
+                self.expr(var);
+                self.token(TokenType::LeftBracket);
+                if let Some(start) = start {
+                    self.expr(start);
+                }
+                self.token(TokenType::Colon);
+                if let Some(end) = end {
+                    self.expr(end);
+                }
+                self.token(TokenType::RightBracket);
+            }
+            Expr::Destructure { pattern, value } => self.destructure(pattern, value),
+            Expr::Get { object, .. } => {
+                self.expr(object);
+                self.token(TokenType::Dot);
+                self.token(TokenType::Identifier);
+            }
+            Expr::Set { object, value, .. } => {
+                self.expr(object);
+                self.token(TokenType::Dot);
+                self.token(TokenType::Identifier);
+                self.operator(TokenType::Equal);
+                self.expr(value);
+            }
+        }
+    }
+
+    fn element(&mut self, var: &Expr, index: &Expr) {
+        self.expr(var);
+        self.token(TokenType::LeftBracket);
+        self.expr(index);
+        self.token(TokenType::RightBracket);
+    }
+
+    // '[a, b] = [b, a]' can also be written as 'a, b = b, a;', the tokens tell which one it was
+    fn destructure(&mut self, pattern: &Pattern, value: &Expr) {
+        let brackets =
+            self.check(TokenType::LeftBracket) && self.list_followed_by(TokenType::Equal);
+        let Pattern::List { elements, .. } = pattern else {
+            panic!("Unreachable.");
+        };
+        if brackets {
+            self.pattern(pattern);
+        } else {
+            self.separated(elements, Self::pattern);
+        }
+        self.operator(TokenType::Equal);
+
+        // 'a, b = [1, 2];' has a single list as its value
+        let single_value = brackets
+            || (self.check(TokenType::LeftBracket) && self.list_followed_by(TokenType::Semicolon));
+        match value {
+            Expr::List(values) if !single_value => self.separated(values, Self::expr),
+            _ => self.expr(value),
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Target(target) => self.expr(target),
+            Pattern::List { elements, .. } => {
+                self.token(TokenType::LeftBracket);
+                self.separated(elements, Self::pattern);
+                self.token(TokenType::RightBracket);
+            }
+        }
+    }
+
+    fn separated<T>(&mut self, items: &[T], mut write: impl FnMut(&mut Self, &T)) {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.token(TokenType::Comma);
+                self.space();
+            }
+            write(self, item);
+        }
+    }
+
+    // writes the next token, which has to be of this kind
+    fn token(&mut self, kind: TokenType) {
+        let token = &self.tokens[self.current];
+        assert_eq!(
+            token.kind, kind,
+            "Formatter out of step on line {}.",
+            token.line
+        );
+
+        let (lexeme, line) = (token.lexeme.clone(), token.line);
+        if !self.line_start {
+            self.inner_comments();
+        }
+        self.write(&lexeme);
+        self.last_line = line;
+        self.current += 1;
+    }
+
+    fn keyword(&mut self, kind: TokenType) {
+        self.token(kind);
+        self.space();
+    }
+
+    fn operator(&mut self, kind: TokenType) {
+        self.space();
+        self.token(kind);
+        self.space();
+    }
+
+    fn space(&mut self) {
+        self.write(" ");
+    }
+
+    fn write(&mut self, text: &str) {
+        if self.line_start {
+            let indent = self.indent + usize::from(self.continued);
+            self.out.push_str(&INDENT.repeat(indent));
+            self.line_start = false;
+        }
+        self.out.push_str(text);
+    }
+
+    fn check(&self, kind: TokenType) -> bool {
+        self.tokens[self.current].kind == kind
+    }
+
+    // whether the list that starts at the current token is followed by a token of this kind
+    fn list_followed_by(&self, kind: TokenType) -> bool {
+        let mut depth = 0;
+        for (i, token) in self.tokens.iter().enumerate().skip(self.current) {
+            match token.kind {
+                TokenType::LeftBracket => depth += 1,
+                TokenType::RightBracket => depth -= 1,
+                _ => continue,
+            }
+            if depth == 0 {
+                return self.tokens[i + 1].kind == kind;
+            }
+        }
+        false
+    }
+
+    fn comment_before(&self, token: usize) -> bool {
+        self.comments
+            .get(self.next_comment)
+            .is_some_and(|comment| comment.before < token)
+    }
+
+    // ends the line with the comments that were on it in the source, after the first one
+    // they get a line of their own
+    fn end_line(&mut self) {
+        let mut first = true;
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            let on_this_line = comment.before < self.current
+                || (comment.before == self.current && comment.trailing);
+            if !on_this_line {
+                break;
+            }
+
+            if first {
+                self.space();
+            } else {
+                self.new_line();
+            }
+            self.write_comment();
+            first = false;
+        }
+        self.new_line();
+        self.continued = false;
+    }
+
+    // the comments in the middle of a statement stay where they are. After a line comment,
+    // or one on its own line, the statement goes on on the next line, indented once more
+    fn inner_comments(&mut self) {
+        while self.comment_before(self.current + 1) {
+            let comment = &self.comments[self.next_comment];
+            let own_line = !comment.trailing;
+            let line_comment = comment.text.starts_with("//");
+
+            if own_line {
+                self.break_line();
+            } else if line_comment && !self.out.ends_with(' ') {
+                self.space();
+            }
+            self.write_comment();
+            if own_line || line_comment {
+                self.break_line();
+            } else {
+                self.space();
+            }
+        }
+    }
+
+    fn break_line(&mut self) {
+        let len = self.out.trim_end_matches(' ').len();
+        self.out.truncate(len);
+        self.new_line();
+        self.continued = true;
+    }
+
+    // the comments on their own lines before the next token
+    fn leading_comments(&mut self) {
+        while self.comment_before(self.current + 1) {
+            let line = self.comments[self.next_comment].line;
+            self.blank_line(line);
+            self.write_comment();
+            self.new_line();
+        }
+    }
+
+    fn write_comment(&mut self) {
+        let comment = self.comments[self.next_comment].clone();
+        self.write(&comment.text);
+        // a comment from the middle of a statement comes after the tokens that followed it
+        let end_line = comment.line + comment.text.matches('\n').count();
+        self.last_line = self.last_line.max(end_line);
+        self.next_comment += 1;
+    }
+
+    // keeps one blank line where the source had one or more
+    fn blank_line(&mut self, line: usize) {
+        if !self.block_start && line > self.last_line + 1 {
+            self.out.push('\n');
+        }
+        self.block_start = false;
+    }
+
+    fn new_line(&mut self) {
+        self.out.push('\n');
+        self.line_start = true;
+    }
+}
+
+fn declaration_keyword(constant: bool) -> TokenType {
+    if constant {
+        TokenType::Const
+    } else {
+        TokenType::Var
+    }
+}
+
+// tokens keep the line they end on, a string can span lines
+fn start_line(token: &Token) -> usize {
+    token.line - token.lexeme.matches('\n').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+
+    fn assert_formats(source: &str, expected: &str) {
+        let formatted = format(source).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format(&formatted).unwrap(), formatted, "not idempotent");
+    }
+
+    #[test]
+    fn layout() {
+        let source = "
+laat x=1+2*-3;laat y;vast [a,[b,c]]=[1,[2,3]];
+proces f(a,b){geef a+b;}
+proces leeg(){}
+klas Geld{nieuw(centen){dit.centen=centen;} plus(ander){geef Geld(dit.centen+ander.centen);}}
+als x>1{zeg\"groot\";}anders als x<0 zeg \"neg\";
+anders{roep x;}
+voor i van 0 tot 3 zeg i;
+voor e in [1,2] { roep e; }
+terwijl nietus {}
+a,b=b,a;a,b=[1,2];[a,b]=[b,a];
+l[0:2];l[:];l[1][2]=3;roep als x dan 1.50 anders 2;
+toets \"x\" { verzeker(wellus); geef; }";
+        let expected = "laat x = 1 + 2 * -3;
+laat y;
+vast [a, [b, c]] = [1, [2, 3]];
+proces f(a, b) {
+    geef a + b;
+}
+proces leeg() {}
+klas Geld {
+    nieuw(centen) {
+        dit.centen = centen;
+    }
+    plus(ander) {
+        geef Geld(dit.centen + ander.centen);
+    }
+}
+als x > 1 {
+    zeg \"groot\";
+} anders als x < 0 zeg \"neg\";
+anders {
+    roep x;
+}
+voor i van 0 tot 3 zeg i;
+voor e in [1, 2] {
+    roep e;
+}
+terwijl nietus {}
+a, b = b, a;
+a, b = [1, 2];
+[a, b] = [b, a];
+l[0:2];
+l[:];
+l[1][2] = 3;
+roep als x dan 1.50 anders 2;
+toets \"x\" {
+    verzeker(wellus);
+    geef;
+}
+";
+        assert_formats(source, expected);
+    }
+
+    #[test]
+    fn annotations() {
+        let source = "laat x:getal=1;laat y :Punt;
+proces f(a:reeks,b,c : niks):proces{geef f;}
+klas Punt{nieuw(x:getal){}}";
+        let expected = "laat x: getal = 1;
+laat y: Punt;
+proces f(a: reeks, b, c: niks): proces {
+    geef f;
+}
+klas Punt {
+    nieuw(x: getal) {}
+}
+";
+        assert_formats(source, expected);
+    }
+
+    #[test]
+    fn comments_and_blank_lines() {
+        let source = "// begin
+
+
+laat x = 1;   // achter
+/* blok
+   commentaar */
+proces f() {
+
+    // binnen
+    geef [1,
+        // midden
+        2];
+    // einde
+}
+laat l = [ // eerste
+    1, /* tweede */ 2];
+als x {
+    zeg 1;
+}
+// anders
+anders {
+    zeg 2;
+}
+als x {} // leeg
+klas K {
+    // geen methodes
+}";
+        let expected = "// begin
+
+laat x = 1; // achter
+/* blok
+   commentaar */
+proces f() {
+    // binnen
+    geef [1,
+        // midden
+        2];
+    // einde
+}
+laat l = [ // eerste
+    1, /* tweede */ 2];
+als x {
+    zeg 1;
+}
+// anders
+anders {
+    zeg 2;
+}
+als x {} // leeg
+klas K {
+    // geen methodes
+}
+";
+        assert_formats(source, expected);
+    }
+
+    #[test]
+    fn errors() {
+        let errors = format("laat x = ;").unwrap_err();
+        assert_eq!(errors[0].line, 1);
+    }
+
+    // formatting only changes the whitespace between the tokens
+    #[test]
+    fn golden_files() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+        for entry in fs::read_dir(dir).unwrap() {
+            let source = fs::read_to_string(entry.unwrap().path()).unwrap();
+            let formatted = format(&source).unwrap();
+            assert_eq!(format(&formatted).unwrap(), formatted);
+
+            let lexemes = |source: &str| -> Vec<String> {
+                let tokens = Scanner::new(source.to_string()).scan_tokens().unwrap();
+                tokens.into_iter().map(|token| token.lexeme).collect()
+            };
+            assert_eq!(lexemes(&source), lexemes(&formatted));
+        }
+    }
+}
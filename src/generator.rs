@@ -0,0 +1,253 @@
+use std::rc::Rc;
+
+use crate::{
+    environment::Env,
+    error::RuntimeErr,
+    expr::Expr,
+    interpreter::Interpreter,
+    iterator::{Iterate, RoxIterator},
+    stmt::Stmt,
+    token::Token,
+    value::Value,
+};
+
+// the state of a call to a function with a 'lever' in it.
+//
+// The interpreter executes statements by recursing into them, which means it can't stop
+// halfway through a loop and continue later. A generator instead keeps track of where it is
+// with a stack of frames, one for every block or loop it is in, and only hands the simple
+// statements over to the interpreter. When it reaches a 'lever' it detaches its scopes
+// from the environment, so they survive until the next value is asked for.
+#[derive(Debug)]
+pub struct Generator {
+    name: Token,
+    // the scopes of the generator while it is suspended, the outermost scope first
+    scopes: Vec<Env>,
+    frames: Vec<Frame>,
+}
+
+#[derive(Debug)]
+enum Frame {
+    Block {
+        statements: Rc<Vec<Stmt>>,
+        next: usize,
+        // whether the block has a scope of its own
+        scoped: bool,
+    },
+    While {
+        condition: Expr,
+        body: Rc<Stmt>,
+    },
+    // both kinds of 'voor' loops, which have a scope for the loop variable
+    For {
+        name: Token,
+        iterator: RoxIterator,
+        body: Rc<Stmt>,
+    },
+}
+impl Frame {
+    fn is_scoped(&self) -> bool {
+        match self {
+            Frame::Block { scoped, .. } => *scoped,
+            Frame::While { .. } => false,
+            Frame::For { .. } => true,
+        }
+    }
+}
+
+impl Generator {
+    // the scope holds the arguments the function was called with
+    pub fn new(name: Token, scope: Env, body: Rc<Vec<Stmt>>) -> Self {
+        Self {
+            name,
+            scopes: vec![scope],
+            frames: vec![Frame::Block {
+                statements: body,
+                next: 0,
+                scoped: true,
+            }],
+        }
+    }
+
+    // runs until the next 'lever', or until the end of the function
+    fn resume(&mut self, interpreter: &mut Interpreter) -> Result<Option<Value>, RuntimeErr> {
+        loop {
+            let Some(frame) = self.frames.last_mut() else {
+                return Ok(None);
+            };
+
+            let value = match frame {
+                Frame::Block {
+                    statements,
+                    next,
+                    scoped,
+                } => {
+                    if *next == statements.len() {
+                        if *scoped {
+                            interpreter.env.kill_youngest_child();
+                        }
+                        self.frames.pop();
+                        continue;
+                    }
+
+                    let statements = Rc::clone(statements);
+                    let index = *next;
+                    *next += 1;
+                    self.execute(&statements[index], interpreter)?
+                }
+
+                Frame::While { condition, body } => {
+                    if let Value::True = interpreter.evaluate_expr(condition)? {
+                        let body = Rc::clone(body);
+                        self.execute(&body, interpreter)?
+                    } else {
+                        self.frames.pop();
+                        continue;
+                    }
+                }
+
+                Frame::For {
+                    name,
+                    iterator,
+                    body,
+                } => match iterator.next(name.line, interpreter)? {
+                    Some(value) => {
+                        interpreter.env.insert_value(&name.lexeme, value);
+                        let body = Rc::clone(body);
+                        self.execute(&body, interpreter)?
+                    }
+                    None => {
+                        interpreter.env.kill_youngest_child();
+                        self.frames.pop();
+                        continue;
+                    }
+                },
+            };
+
+            if value.is_some() {
+                return Ok(value);
+            }
+        }
+    }
+
+    // returns the value of a 'lever'. Blocks and loops are pushed as a frame,
+    // they are executed by 'resume'
+    fn execute(
+        &mut self,
+        stmt: &Stmt,
+        interpreter: &mut Interpreter,
+    ) -> Result<Option<Value>, RuntimeErr> {
+        match stmt {
+            Stmt::Yield { expr, .. } => return Ok(Some(interpreter.evaluate_expr(expr)?)),
+
+            Stmt::Block(statements) => {
+                interpreter.env.create_new_child();
+                self.frames.push(Frame::Block {
+                    statements: Rc::clone(statements),
+                    next: 0,
+                    scoped: true,
+                });
+            }
+
+            Stmt::If {
+                first_if,
+                else_ifs,
+                final_else,
+            } => {
+                for branch in std::iter::once(first_if).chain(else_ifs) {
+                    if let Value::True = interpreter.evaluate_expr(&branch.should_execute)? {
+                        return self.execute(&branch.statement, interpreter);
+                    }
+                }
+                if let Some(final_else) = final_else {
+                    return self.execute(final_else, interpreter);
+                }
+            }
+
+            Stmt::While {
+                condition, body, ..
+            } => self.frames.push(Frame::While {
+                condition: condition.clone(),
+                body: Rc::clone(body),
+            }),
+
+            Stmt::For {
+                name,
+                start,
+                end,
+                body,
+            } => {
+                let iterator = interpreter.range(name, start, end)?;
+                self.push_for(name, iterator, body, interpreter);
+            }
+
+            Stmt::ForEach {
+                name,
+                iterable,
+                body,
+            } => {
+                let iterable = interpreter.evaluate_expr(iterable)?;
+                let iterator = interpreter.iterate(&iterable, name.line)?;
+                self.push_for(name, iterator, body, interpreter);
+            }
+
+            _ => interpreter.evaluate_stmt(stmt)?,
+        }
+        Ok(None)
+    }
+
+    fn push_for(
+        &mut self,
+        name: &Token,
+        iterator: RoxIterator,
+        body: &Rc<Stmt>,
+        interpreter: &mut Interpreter,
+    ) {
+        interpreter.env.create_new_child();
+        self.frames.push(Frame::For {
+            name: name.clone(),
+            iterator,
+            body: Rc::clone(body),
+        });
+    }
+}
+
+impl Iterate for Generator {
+    fn next(&mut self, interpreter: &mut Interpreter) -> Result<Option<Value>, RuntimeErr> {
+        for scope in self.scopes.drain(..) {
+            interpreter.env.push_child(scope);
+        }
+
+        interpreter.call_stack.push(self.name.clone());
+        let result = self.resume(interpreter);
+        interpreter.call_stack.pop();
+        let scopes = self.frames.iter().filter(|frame| frame.is_scoped()).count();
+
+        match result {
+            Ok(Some(value)) => {
+                for _ in 0..scopes {
+                    self.scopes
+                        .push(interpreter.env.take_youngest_child().unwrap());
+                }
+                self.scopes.reverse();
+                Ok(Some(value))
+            }
+            // 'geef' ends the generator, just like reaching the end of the function or an error
+            result => {
+                for _ in 0..scopes {
+                    interpreter.env.kill_youngest_child();
+                }
+                self.frames.clear();
+
+                match result {
+                    Err(RuntimeErr::Return { .. }) => Ok(None),
+                    result => result,
+                }
+            }
+        }
+    }
+
+    fn to_string(&self) -> String {
+        format!("generator {}", self.name.lexeme)
+    }
+}
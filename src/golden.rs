@@ -0,0 +1,96 @@
+// runs every tests/*.rox file the way 'rox' runs file.rox, and compares what it
+// printed with the comments in the file:
+//
+//   roep 1 + 2;      // verwacht: 3
+//   roep [1][5];     // verwacht fout: Index 5 valt buiten een lijst met lengte 1.
+//   laat klok = 1;   // verwacht waarschuwing: 'klok' overschaduwt een ingebouwde functie.
+//
+// Every 'verwacht' is one line of output, in the order of the file. Errors and
+// warnings have to be reported on the line of their comment
+use std::{fs, path::Path, thread};
+
+use crate::{
+    engine::Engine, error::Diagnostic, interpreter::DEFAULT_MAX_CALL_DEPTH, output::Buffer,
+    permissions::Permissions, rox::STACK_SIZE,
+};
+
+const OUTPUT: &str = "// verwacht: ";
+const ERROR: &str = "// verwacht fout: ";
+const WARNING: &str = "// verwacht waarschuwing: ";
+
+#[derive(Debug, Default, PartialEq)]
+struct Run {
+    output: String,
+    errors: Vec<Diagnostic>,
+    warnings: Vec<Diagnostic>,
+}
+
+fn expected(source: &str) -> Run {
+    let mut run = Run::default();
+    for (i, line) in source.lines().enumerate() {
+        if let Some((_, output)) = line.split_once(OUTPUT) {
+            run.output.push_str(output);
+            run.output.push('\n');
+        } else if let Some((_, error)) = line.split_once(ERROR) {
+            run.errors.push(Diagnostic::new(i + 1, error));
+        } else if let Some((_, warning)) = line.split_once(WARNING) {
+            run.warnings.push(Diagnostic::new(i + 1, warning));
+        }
+    }
+    run
+}
+
+fn run(source: String) -> Run {
+    let output = Buffer::new();
+    let mut engine = Engine::with_permissions(&Permissions::all());
+    engine.set_max_call_depth(DEFAULT_MAX_CALL_DEPTH);
+    engine.set_output(output.clone());
+
+    let compiled = engine.compile(&source);
+    let warnings = engine.warnings().to_vec();
+    let errors = match compiled {
+        Ok(statements) => engine.interpreter.interpret(statements).0,
+        Err(err) => err.diagnostics().to_vec(),
+    };
+
+    Run {
+        output: output.contents(),
+        errors,
+        warnings,
+    }
+}
+
+#[test]
+fn golden_files() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rox"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "No tests/*.rox files found.");
+
+    let mut failures = Vec::new();
+    for path in paths {
+        let source = fs::read_to_string(&path).unwrap();
+        let expected = expected(&source);
+
+        let actual = thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(move || run(source))
+            .unwrap()
+            .join()
+            .unwrap();
+
+        if actual != expected {
+            failures.push(format!(
+                "{}\nverwacht: {:#?}\nkreeg: {:#?}",
+                path.display(),
+                expected,
+                actual
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
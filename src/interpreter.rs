@@ -1,67 +1,193 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    io::{self, Write},
+    rc::Rc,
+};
+
 use crate::{
-    callable::{Callable, Clock, Factorial, Fibonacci, Len},
+    bigint::BigInt,
+    callable::{
+        Assert, AssertEqual, Callable, Clock, Collect, EnvVar, Factorial, Fibonacci, Len, Lines,
+        Next, ReadFile, RunCommand, Take, WriteFile,
+    },
+    class::{operator_method, BoundMethod, Class, Instance, INDEX_METHOD},
     environment::Env,
-    error::{rox_error, RuntimeErr},
-    expr::Expr,
+    error::{Diagnostic, EvalError, RuntimeErr},
+    expr::{Expr, Pattern},
+    iterator::{Range, RoxIterator},
+    limits::{self, Limits, Usage},
+    permissions::{Capability, Permissions},
     stmt::{If, Stmt},
     token::Token,
     token_type::TokenType,
     value::Value,
 };
 
+// every call of a Rox function nests a few calls deeper on the Rust stack,
+// so the depth is limited before the stack runs out
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
 pub struct Interpreter {
     pub env: Env,
+    // the names of the functions that are running, the innermost last
+    pub call_stack: Vec<Token>,
+    pub max_call_depth: usize,
+    pub limits: Limits,
+    usage: Usage,
+    // the builtins the permissions left out, and the capability they need
+    pub forbidden: HashMap<String, Capability>,
+    // where 'zeg' and 'roep' write to
+    pub output: Box<dyn Write>,
 }
 impl Interpreter {
-    pub fn new() -> Self {
+    pub fn new(permissions: &Permissions) -> Self {
         let mut env = Env::new();
+        let mut forbidden = HashMap::new();
 
         macro_rules! insert_global_function {
             ($type: ident) => {
                 let func = Value::Callable(Box::new($type {}));
                 env.insert_global_value($type.to_string(), func);
             };
+            ($type: ident, $capability: ident) => {
+                if permissions.allows(Capability::$capability) {
+                    insert_global_function!($type);
+                } else {
+                    forbidden.insert($type.to_string(), Capability::$capability);
+                }
+            };
         }
         insert_global_function!(Factorial);
         insert_global_function!(Clock);
         insert_global_function!(Fibonacci);
         insert_global_function!(Len);
+        insert_global_function!(Collect);
+        insert_global_function!(Take);
+        insert_global_function!(Next);
+        insert_global_function!(Assert);
+        insert_global_function!(AssertEqual);
+        insert_global_function!(Lines, ReadFiles);
+        insert_global_function!(ReadFile, ReadFiles);
+        insert_global_function!(WriteFile, WriteFiles);
+        insert_global_function!(EnvVar, Env);
+        insert_global_function!(RunCommand, Process);
+
+        Self {
+            env,
+            call_stack: Vec::new(),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            limits: Limits::default(),
+            usage: Usage::default(),
+            forbidden,
+            output: Box::new(io::stdout()),
+        }
+    }
 
-        Self { env }
+    // keeps going after a runtime error, the errors are returned along with
+    // the value of a 'geef' outside of any function
+    pub fn interpret(&mut self, statements: Vec<Stmt>) -> (Vec<Diagnostic>, Value) {
+        let (errors, return_val) = self.execute(&statements, true);
+        let errors = errors
+            .iter()
+            .flat_map(EvalError::diagnostics)
+            .cloned()
+            .collect();
+        (errors, return_val)
     }
 
-    pub fn interpret(&mut self, statements: Vec<Stmt>) -> (bool, Value) {
-        let mut error_found = false;
+    // stops at the first runtime error. A 'geef' outside of any function
+    // ends the program with its value
+    pub fn run(&mut self, statements: &[Stmt]) -> Result<Value, EvalError> {
+        let (errors, return_val) = self.execute(statements, false);
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(return_val),
+        }
+    }
+
+    // runs the statements of a program. With 'keep_going' an error or a 'geef' outside
+    // of any function doesn't stop it, a limit always does
+    fn execute(&mut self, statements: &[Stmt], keep_going: bool) -> (Vec<EvalError>, Value) {
+        self.usage = Usage::start(&self.limits);
+        let mut errors = Vec::new();
         let mut return_val = Value::Nil;
 
         for statement in statements {
-            if let Err(e) = self.evaluate_stmt(&statement) {
-                error_found = true;
-
-                match e {
-                    RuntimeErr::Err(line, msg) => rox_error(line, &msg),
-                    RuntimeErr::Return { value } => {
-                        rox_error(0, "Onverwachtte geef.");
-                        return_val = value;
-                    }
+            let error = match self.evaluate_stmt(statement) {
+                Ok(()) => continue,
+                Err(RuntimeErr::Err(line, msg)) => EvalError::Runtime(Diagnostic::new(line, msg)),
+                Err(RuntimeErr::Limit(line, msg)) => {
+                    errors.push(EvalError::Limit(Diagnostic::new(line, msg)));
+                    break;
                 }
+                Err(RuntimeErr::Return { value }) if !keep_going => return (errors, value),
+                Err(RuntimeErr::Return { value }) => {
+                    return_val = value;
+                    EvalError::Runtime(Diagnostic::new(0, "Onverwachtte geef."))
+                }
+                Err(RuntimeErr::TailCall { .. }) => panic!("Unreachable."),
+            };
+            errors.push(error);
+            if !keep_going {
+                break;
             }
         }
-        (error_found, return_val)
+        (errors, return_val)
     }
 
     pub fn evaluate_stmt(&mut self, stmt: &Stmt) -> Result<(), RuntimeErr> {
+        self.usage
+            .step(&self.limits)
+            .map_err(|msg| limit_err(stmt.line(), msg))?;
+
         match stmt {
             Stmt::Expr(expr) => {
                 self.evaluate_expr(expr)?;
             }
 
-            Stmt::Print(expr) => print!("{}", (self.evaluate_expr(expr)?).to_string()),
-            Stmt::Println(expr) => println!("{}", (self.evaluate_expr(expr)?).to_string()),
+            Stmt::Print(expr) => {
+                let value = self.evaluate_expr(expr)?;
+                self.write_output(expr, format_args!("{}", value))?;
+            }
+            Stmt::Println(expr) => {
+                let value = self.evaluate_expr(expr)?;
+                self.write_output(expr, format_args!("{}\n", value))?;
+            }
+
+            Stmt::Var {
+                name,
+                expr,
+                constant,
+                ..
+            } => {
+                let value = self.evaluate_expr(expr)?;
+                if *constant {
+                    self.env.insert_constant(&name.lexeme, value);
+                } else {
+                    self.env.insert_value(&name.lexeme, value);
+                }
+            }
 
-            Stmt::Var { name, expr } => {
+            Stmt::Destructure {
+                pattern,
+                expr,
+                constant,
+            } => {
                 let value = self.evaluate_expr(expr)?;
-                self.env.insert_value(&name.lexeme, value);
+
+                let mut targets = Vec::new();
+                unpack(pattern, value, &mut targets)?;
+                for (target, value) in targets {
+                    let Expr::Var(name) = target else {
+                        panic!("Unreachable.");
+                    };
+                    if *constant {
+                        self.env.insert_constant(&name.lexeme, value);
+                    } else {
+                        self.env.insert_value(&name.lexeme, value);
+                    }
+                }
             }
 
             Stmt::Block(statements) => self.evaluate_block_stmt(statements)?,
@@ -72,7 +198,9 @@ impl Interpreter {
                 final_else,
             } => self.evaluate_if_stmt(first_if, else_ifs, final_else)?,
 
-            Stmt::While { condition, body } => {
+            Stmt::While {
+                condition, body, ..
+            } => {
                 while let Value::True = self.evaluate_expr(condition)? {
                     self.evaluate_stmt(body)?;
                 }
@@ -83,7 +211,25 @@ impl Interpreter {
                 start,
                 end,
                 body,
-            } => self.evaluate_for_stmt(name, start, end, body)?,
+            } => {
+                let iterator = self.range(name, start, end)?;
+                self.evaluate_for_stmt(name, iterator, body)?;
+            }
+
+            Stmt::ForEach {
+                name,
+                iterable,
+                body,
+            } => {
+                let iterable = self.evaluate_expr(iterable)?;
+                let iterator = self.iterate(&iterable, name.line)?;
+                self.evaluate_for_stmt(name, iterator, body)?;
+            }
+
+            Stmt::Class { name, methods } => {
+                let class = Value::Callable(Box::new(Class::new(name.clone(), methods.clone())));
+                self.env.insert_value(&name.lexeme, class);
+            }
 
             Stmt::Function(funtion) => {
                 let function = Value::Callable(Box::new(funtion.clone()));
@@ -92,13 +238,26 @@ impl Interpreter {
 
             Stmt::Return { expr, .. } => {
                 return Err(RuntimeErr::Return {
-                    value: self.evaluate_expr(expr)?,
+                    value: self.evaluate_tail_expr(expr)?,
                 });
             }
+
+            // the resolver only allows 'lever' in functions, and those are run by a Generator
+            Stmt::Yield { .. } => panic!("Unreachable."),
+
+            // tests only run with 'rox test'
+            Stmt::Test { .. } => (),
         }
         Ok(())
     }
 
+    fn write_output(&mut self, expr: &Expr, args: std::fmt::Arguments) -> Result<(), RuntimeErr> {
+        self.output.write_fmt(args).map_err(|err| {
+            let msg = format!("Kon de uitvoer niet schrijven: {}.", err);
+            RuntimeErr::Err(expr.line().unwrap_or(0), msg)
+        })
+    }
+
     fn evaluate_block_stmt(&mut self, statements: &Vec<Stmt>) -> Result<(), RuntimeErr> {
         self.env.create_new_child();
         for stmt in statements {
@@ -115,7 +274,7 @@ impl Interpreter {
         &mut self,
         first_if: &If,
         else_ifs: &Vec<If>,
-        other: &Option<Box<Stmt>>,
+        other: &Option<Rc<Stmt>>,
     ) -> Result<(), RuntimeErr> {
         if let Value::True = self.evaluate_expr(&first_if.should_execute)? {
             // execute the first if
@@ -141,58 +300,88 @@ impl Interpreter {
         }
     }
 
-    fn evaluate_for_stmt(
+    // the numbers that 'voor name van start tot end' goes through
+    pub fn range(
         &mut self,
         name: &Token,
         start: &Expr,
         end: &Expr,
-        statement: &Stmt,
-    ) -> Result<(), RuntimeErr> {
-        let start_value = self.evaluate_expr(start)?;
-        let end_value = self.evaluate_expr(end)?;
+    ) -> Result<RoxIterator, RuntimeErr> {
+        let start = self.evaluate_expr(start)?;
+        let end = self.evaluate_expr(end)?;
 
-        if let (Value::Num(mut current), Value::Num(end)) = (start_value, end_value) {
-            self.env.create_new_child();
-            self.env.insert_value(&name.lexeme, Value::Num(current));
-
-            while current < end {
-                if let Err(err) = self.evaluate_stmt(statement) {
-                    self.env.kill_youngest_child();
-                    return Err(err);
-                }
+        match Range::new(&start, &end) {
+            Some(range) => Ok(RoxIterator::new(range)),
+            None => Err(RuntimeErr::Err(
+                name.line,
+                "De grenzen van een voor lus moeten nummers zijn.".to_string(),
+            )),
+        }
+    }
 
-                current += 1.;
-                if let Err(err) = self.env.replace_value(name, &Value::Num(current)) {
-                    self.env.kill_youngest_child();
-                    return Err(err);
-                }
+    pub fn iterate(&self, value: &Value, line: usize) -> Result<RoxIterator, RuntimeErr> {
+        match value.iter() {
+            Some(iterator) => Ok(iterator),
+            None => {
+                let msg = format!("Je kan niet over '{}' itereren.", value);
+                Err(RuntimeErr::Err(line, msg))
             }
+        }
+    }
 
-            while current > end {
-                self.evaluate_stmt(statement)?;
+    fn evaluate_for_stmt(
+        &mut self,
+        name: &Token,
+        iterator: RoxIterator,
+        statement: &Stmt,
+    ) -> Result<(), RuntimeErr> {
+        self.env.create_new_child();
+        let result = self.evaluate_for_loop(name, iterator, statement);
+        self.env.kill_youngest_child();
+        result
+    }
 
-                current -= 1.;
-                if let Err(err) = self.env.replace_value(name, &Value::Num(current)) {
-                    self.env.kill_youngest_child();
-                    return Err(err);
-                }
-            }
-            self.env.kill_youngest_child();
-            Ok(())
-        } else {
-            panic!("Unreachable.");
+    fn evaluate_for_loop(
+        &mut self,
+        name: &Token,
+        iterator: RoxIterator,
+        statement: &Stmt,
+    ) -> Result<(), RuntimeErr> {
+        while let Some(value) = iterator.next(name.line, self)? {
+            self.env.insert_value(&name.lexeme, value);
+            self.evaluate_stmt(statement)?;
         }
+        Ok(())
     }
 
     pub fn evaluate_expr(&mut self, expr: &Expr) -> Result<Value, RuntimeErr> {
-        match expr {
+        self.usage
+            .step(&self.limits)
+            .map_err(|msg| limit_err(expr.line(), msg))?;
+
+        let value = match expr {
             Expr::Lit(lit) => Ok(Value::from_lit(lit)),
             Expr::Grouping(expr) => self.evaluate_expr(expr),
-            Expr::Unary(token, expr) => self.evaluate_unary_expr(token, expr),
-            Expr::Binary(left, op, right) => self.evaluate_binary_expr(left, op, right),
-            Expr::Var(token) => self.evaluate_var_expr(token),
-            Expr::AssignToExpr(name, expr) => self.evaluate_assign_expr(name, expr),
+            Expr::Unary(token, operand) => self
+                .evaluate_unary_expr(token, operand)
+                .and_then(|value| self.allocated(expr.line(), value)),
+            Expr::Binary(left, op, right) => self
+                .evaluate_binary_expr(left, op, right)
+                .and_then(|value| self.allocated(expr.line(), value)),
+            Expr::Var(token) => self
+                .evaluate_var_expr(token)
+                .and_then(|value| self.allocated(expr.line(), value)),
+            Expr::Assign(name, expr) => self.evaluate_assign_expr(name, expr),
             Expr::Logic(left, op, right) => self.evaluate_logic_expr(left, op, right),
+            Expr::Conditional {
+                keyword,
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let branch = self.choose_branch(keyword, condition, then_branch, else_branch)?;
+                self.evaluate_expr(branch)
+            }
             Expr::Call(callee, right_paren, args) => {
                 self.evaluate_call_expr(callee, right_paren, args)
             }
@@ -202,12 +391,48 @@ impl Interpreter {
                 index,
                 right_bracket,
             } => self.evaluate_element_expr(var, index, right_bracket),
+            Expr::Slice {
+                var,
+                start,
+                end,
+                right_bracket,
+            } => self
+                .evaluate_slice_expr(var, start, end, right_bracket)
+                .and_then(|value| self.allocated(expr.line(), value)),
             Expr::AssignToElement {
-                ref var,
-                ref index,
-                ref value,
-            } => self.evaluate_assign_to_element_expr(var, index, value),
-        }
+                var,
+                index,
+                right_bracket,
+                value,
+            } => self.evaluate_assign_to_element_expr(var, index, right_bracket, value),
+            Expr::Destructure { pattern, value } => self.evaluate_destructure_expr(pattern, value),
+            Expr::Get { object, name } => {
+                let object = self.evaluate_expr(object)?;
+                instance(&object, name)?.get(name)
+            }
+            Expr::Set {
+                object,
+                name,
+                value,
+            } => {
+                self.check_not_constant(object)?;
+                let object = self.evaluate_expr(object)?;
+                let value = self.evaluate_expr(value)?;
+                instance(&object, name)?.set(name, value);
+                Ok(Value::Nil)
+            }
+            Expr::This(keyword) => self.evaluate_var_expr(keyword),
+        }?;
+        Ok(value)
+    }
+
+    // counts a list, string or big integer that is made or copied against the allocation
+    // limit. Only the expressions that make one count it, not every expression it passes
+    fn allocated(&mut self, line: Option<usize>, value: Value) -> Result<Value, RuntimeErr> {
+        self.usage
+            .allocate(&self.limits, limits::size(&value))
+            .map_err(|msg| limit_err(line, msg))?;
+        Ok(value)
     }
 
     fn evaluate_list_expr(&mut self, elements: &Vec<Expr>) -> Result<Value, RuntimeErr> {
@@ -218,6 +443,10 @@ impl Interpreter {
             new_elements.push(value);
         }
 
+        // the elements were counted when they were made
+        self.usage
+            .allocate(&self.limits, new_elements.len())
+            .map_err(|msg| limit_err(elements.first().and_then(Expr::line), msg))?;
         Ok(Value::List(new_elements))
     }
 
@@ -227,20 +456,36 @@ impl Interpreter {
         index: &Expr,
         right_bracket: &Token,
     ) -> Result<Value, RuntimeErr> {
-        let index = self.evaluate_expr(index)?;
-        let index = match index {
-            Value::Num(num) => num,
-            _ => {
-                return Err(RuntimeErr::Err(
-                    right_bracket.line,
-                    "Index is geen nummer.".to_string(),
-                ))
+        // 'l[i]' reads the element straight from the variable, copying the whole list for
+        // every element would make a loop over it quadratic
+        let (var, index) = match var {
+            Expr::Var(name) => {
+                let index = self.evaluate_expr(index)?;
+                if let Some(Value::List(elements)) = self.env.value(name) {
+                    let index = list_index(&index, elements.len(), right_bracket)?;
+                    return Ok(elements[index].clone());
+                }
+                (self.evaluate_expr(var)?, index)
             }
+            var => (self.evaluate_expr(var)?, self.evaluate_expr(index)?),
         };
 
-        let var = self.evaluate_expr(var)?;
         match var {
-            Value::List(elements) => Ok(elements[index as usize].clone()),
+            Value::List(elements) => {
+                let index = list_index(&index, elements.len(), right_bracket)?;
+                Ok(elements[index].clone())
+            }
+            Value::Instance(instance) => {
+                let Some(method) = instance.method(INDEX_METHOD) else {
+                    let msg = format!(
+                        "'{}' heeft geen methode '{}'.",
+                        instance.class_name(),
+                        INDEX_METHOD
+                    );
+                    return Err(RuntimeErr::Err(right_bracket.line, msg));
+                };
+                self.call_operator_method(method, index, right_bracket)
+            }
             _ => Err(RuntimeErr::Err(
                 right_bracket.line,
                 "Variabele is geen lijst.".to_string(),
@@ -248,31 +493,221 @@ impl Interpreter {
         }
     }
 
+    fn evaluate_slice_expr(
+        &mut self,
+        var: &Expr,
+        start: &Option<Box<Expr>>,
+        end: &Option<Box<Expr>>,
+        right_bracket: &Token,
+    ) -> Result<Value, RuntimeErr> {
+        let var = self.evaluate_expr(var)?;
+        let Value::List(elements) = var else {
+            return Err(RuntimeErr::Err(
+                right_bracket.line,
+                "Variabele is geen lijst.".to_string(),
+            ));
+        };
+
+        let start = match start {
+            Some(start) => {
+                let start = self.evaluate_expr(start)?;
+                slice_bound(&start, elements.len(), right_bracket)?
+            }
+            None => 0,
+        };
+        let end = match end {
+            Some(end) => {
+                let end = self.evaluate_expr(end)?;
+                slice_bound(&end, elements.len(), right_bracket)?
+            }
+            None => elements.len(),
+        };
+
+        if start >= end {
+            return Ok(Value::List(Vec::new()));
+        }
+        Ok(Value::List(elements[start..end].to_vec()))
+    }
+
     fn evaluate_assign_to_element_expr(
         &mut self,
         var: &Expr,
         index: &Expr,
+        right_bracket: &Token,
         value: &Expr,
     ) -> Result<Value, RuntimeErr> {
-        match var {
-            Expr::Var(name) => {
-                let index = self.evaluate_expr(index)?;
-                let index = match index {
-                    Value::Num(num) => num as usize,
-                    _ => {
-                        return Err(RuntimeErr::Err(
-                            name.line,
-                            "Index is geen nummer.".to_string(),
-                        ))
-                    }
-                };
+        let value = self.evaluate_expr(value)?;
+        self.assign_to_element(var, index, right_bracket, value)?;
+        Ok(Value::Nil)
+    }
 
-                let value = self.evaluate_expr(value)?;
-                self.env.replace_element(&name, index, &value)?;
-                Ok(Value::Nil)
+    // the properties of an instance that is reached through a constant, like 'l[0].p' for
+    // 'vast l', can't be changed either. A method can still change 'dit'
+    fn check_not_constant(&self, object: &Expr) -> Result<(), RuntimeErr> {
+        match target_root(object) {
+            Some(name) => self.env.check_not_constant(name),
+            None => Ok(()),
+        }
+    }
+
+    // assigns to a path like 'matrix[i][j]'. The list is changed in place in its variable,
+    // or in its field for 'p.lijst[0]'. If the path doesn't start at either, like 'f()[0]',
+    // only the temporary list is changed
+    fn assign_to_element(
+        &mut self,
+        var: &Expr,
+        index: &Expr,
+        right_bracket: &Token,
+        value: Value,
+    ) -> Result<(), RuntimeErr> {
+        let mut path = vec![(index, right_bracket)];
+        let mut root = var;
+        while let Expr::Element {
+            var,
+            index,
+            right_bracket,
+        } = root
+        {
+            path.push((index, right_bracket));
+            root = var;
+        }
+        path.reverse();
+
+        let mut temporary = Value::Nil;
+        let mut owner = None;
+        match root {
+            Expr::Var(_) => (),
+            Expr::Get { object, name } => {
+                self.check_not_constant(object)?;
+                let object = self.evaluate_expr(object)?;
+                owner = Some(instance(&object, name)?.clone());
             }
-            _ => panic!("Unreachable."),
+            _ => temporary = self.evaluate_expr(root)?,
+        }
+        let mut indices = Vec::new();
+        for (index, right_bracket) in path {
+            indices.push((self.evaluate_expr(index)?, right_bracket));
+        }
+
+        match (root, owner) {
+            (Expr::Var(name), _) => set_element(self.env.value_mut(name)?, &indices, value),
+            (Expr::Get { name, .. }, Some(owner)) => {
+                owner.update(name, |field| set_element(field, &indices, value))
+            }
+            _ => set_element(&mut temporary, &indices, value),
+        }
+    }
+
+    fn evaluate_destructure_expr(
+        &mut self,
+        pattern: &Pattern,
+        value: &Expr,
+    ) -> Result<Value, RuntimeErr> {
+        // the whole right side is evaluated first, so 'a, b = b, a' swaps a and b
+        let value = self.evaluate_expr(value)?;
+
+        let mut targets = Vec::new();
+        unpack(pattern, value, &mut targets)?;
+        for (target, value) in targets {
+            match target {
+                Expr::Var(name) => self.env.replace_value(name, &value)?,
+                Expr::Element {
+                    var,
+                    index,
+                    right_bracket,
+                } => self.assign_to_element(var, index, right_bracket, value)?,
+                Expr::Get { object, name } => {
+                    self.check_not_constant(object)?;
+                    let object = self.evaluate_expr(object)?;
+                    instance(&object, name)?.set(name, value);
+                }
+                _ => panic!("Unreachable."),
+            }
+        }
+        Ok(Value::Nil)
+    }
+
+    fn choose_branch<'a>(
+        &mut self,
+        keyword: &Token,
+        condition: &Expr,
+        then_branch: &'a Expr,
+        else_branch: &'a Expr,
+    ) -> Result<&'a Expr, RuntimeErr> {
+        match self.evaluate_expr(condition)?.is_true() {
+            Some(true) => Ok(then_branch),
+            Some(false) => Ok(else_branch),
+            None => {
+                let msg = "'als' kan alleen worden gebruikt op waardigheids waarden.".to_string();
+                Err(RuntimeErr::Err(keyword.line, msg))
+            }
+        }
+    }
+
+    // the expression of a 'geef'. When it calls the function that is running, like
+    // 'geef f(n - 1)' inside f, the call becomes a TailCall so that f starts over
+    // instead of recursing. Both branches of an 'als' expression are in tail position too
+    fn evaluate_tail_expr(&mut self, expr: &Expr) -> Result<Value, RuntimeErr> {
+        match expr {
+            Expr::Grouping(expr) => self.evaluate_tail_expr(expr),
+            Expr::Conditional {
+                keyword,
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let branch = self.choose_branch(keyword, condition, then_branch, else_branch)?;
+                self.evaluate_tail_expr(branch)
+            }
+            Expr::Call(callee, right_paren, args) => {
+                let callee = self.evaluate_expr(callee)?;
+                let arguments = self.evaluate_arguments(args)?;
+
+                if let Value::Callable(function) = &callee {
+                    if self.is_running(function.as_ref()) {
+                        check_arity(function.as_ref(), &arguments, right_paren)?;
+                        return Err(RuntimeErr::TailCall { arguments });
+                    }
+                }
+                self.call(callee, arguments, right_paren)
+            }
+            _ => self.evaluate_expr(expr),
+        }
+    }
+
+    // the line is where the call that would go too deep is made
+    pub fn check_call_depth(&self, line: usize) -> Result<(), RuntimeErr> {
+        if self.call_stack.len() >= self.max_call_depth {
+            let msg = format!(
+                "Maximale recursiediepte van {} bereikt.",
+                self.max_call_depth
+            );
+            return Err(RuntimeErr::Err(line, msg));
         }
+        Ok(())
+    }
+
+    // for builtins that loop for a long time, so the step limit and the timeout hold there too
+    pub fn step(&mut self) -> Result<(), RuntimeErr> {
+        self.usage
+            .step(&self.limits)
+            .map_err(|msg| limit_err(None, msg))
+    }
+
+    // for builtins that make an integer of about this many bits, see 'Usage::reserve_bits'
+    pub fn reserve_bits(&self, bits: u64) -> Result<(), RuntimeErr> {
+        self.usage
+            .reserve_bits(&self.limits, bits)
+            .map_err(|msg| limit_err(None, msg))
+    }
+
+    fn is_running(&self, callee: &dyn Callable) -> bool {
+        let (Some(function), Some(running)) = (callee.as_function(), self.call_stack.last()) else {
+            return false;
+        };
+        !function.is_generator
+            && function.name.lexeme == running.lexeme
+            && function.name.line == running.line
     }
 
     fn evaluate_call_expr(
@@ -282,38 +717,87 @@ impl Interpreter {
         args: &Vec<Expr>,
     ) -> Result<Value, RuntimeErr> {
         let callee = self.evaluate_expr(callee)?;
+        let arguments = self.evaluate_arguments(args)?;
+        self.call(callee, arguments, right_paren)
+    }
 
+    fn evaluate_arguments(&mut self, args: &Vec<Expr>) -> Result<Vec<Value>, RuntimeErr> {
         let mut arguments = Vec::new();
         for arg in args {
             arguments.push(self.evaluate_expr(arg)?);
         }
+        Ok(arguments)
+    }
 
+    fn call(
+        &mut self,
+        callee: Value,
+        arguments: Vec<Value>,
+        right_paren: &Token,
+    ) -> Result<Value, RuntimeErr> {
         if let Value::Callable(callee) = callee {
-            if callee.arity() != arguments.len() {
-                let msg = format!(
-                    "Verwachtte {} argumenten maar kreeg er {}.",
-                    callee.arity(),
-                    arguments.len(),
-                );
-                return Err(RuntimeErr::Err(right_paren.line, msg));
+            check_arity(callee.as_ref(), &arguments, right_paren)?;
+            self.check_call_depth(right_paren.line)?;
+            let value = callee.call(arguments, self).map_err(|err| match err {
+                // builtins don't know where they were called from
+                RuntimeErr::Err(0, msg) => RuntimeErr::Err(right_paren.line, msg),
+                RuntimeErr::Limit(0, msg) => RuntimeErr::Limit(right_paren.line, msg),
+                err => err,
+            })?;
+            // what a function gives was counted where it was made, a builtin makes its own
+            if callee.line().is_none() {
+                return self.allocated(Some(right_paren.line), value);
             }
-            callee.call(arguments, self)
+            Ok(value)
         } else {
             let msg = "Je kan alleen functies en klassen bellen.".to_string();
             Err(RuntimeErr::Err(right_paren.line, msg))
         }
     }
+
+    // calls a method like 'plus' or 'index' that an operator stands for
+    fn call_operator_method(
+        &mut self,
+        method: BoundMethod,
+        argument: Value,
+        token: &Token,
+    ) -> Result<Value, RuntimeErr> {
+        if method.arity() != 1 {
+            let msg = format!(
+                "'{}' moet precies één parameter hebben.",
+                method.to_string()
+            );
+            return Err(RuntimeErr::Err(token.line, msg));
+        }
+        self.check_call_depth(token.line)?;
+        method.call(vec![argument], self)
+    }
+
     fn evaluate_unary_expr(&mut self, token: &Token, expr: &Expr) -> Result<Value, RuntimeErr> {
         let right = self.evaluate_expr(expr)?;
 
         match token.kind {
             TokenType::Minus => match right {
+                Value::Int(int) => match int.checked_neg() {
+                    Some(int) => Ok(Value::Int(int)),
+                    None => Ok(Value::Big(-&BigInt::from(int))),
+                },
+                Value::Big(big) => Ok(Value::from_big(-&big)),
                 Value::Num(num) => Ok(Value::Num(-num)),
                 _ => Err(RuntimeErr::Err(
                     token.line,
                     "Min kan alleen worden gebruikt voor nummers.".to_string(),
                 )),
             },
+            // ~x is -x - 1 for every integer, which is what flipping all bits in two's complement gives
+            TokenType::Tilde => match right {
+                Value::Int(int) => Ok(Value::Int(!int)),
+                Value::Big(big) => Ok(Value::from_big(&(-&big) - &BigInt::from(1))),
+                _ => Err(RuntimeErr::Err(
+                    token.line,
+                    "'~' kan alleen worden gebruikt op gehele getallen.".to_string(),
+                )),
+            },
             TokenType::Bang => match right.is_true() {
                 Some(bool) => Ok(Value::from_bool(!bool)),
                 None => Err(RuntimeErr::Err(
@@ -334,68 +818,213 @@ impl Interpreter {
         let left = self.evaluate_expr(left)?;
         let right = self.evaluate_expr(right)?;
 
+        // an instance on the left decides for itself what the operator does
+        if let (Value::Instance(instance), Some(name)) = (&left, operator_method(op.kind)) {
+            match instance.method(name) {
+                Some(method) => {
+                    let result = self.call_operator_method(method, right, op)?;
+                    if !matches!(
+                        op.kind,
+                        TokenType::EqualEqual | TokenType::BangEqual | TokenType::Less
+                    ) {
+                        return Ok(result);
+                    }
+
+                    return match result.is_true() {
+                        Some(is_true) => Ok(Value::from_bool(
+                            is_true != (op.kind == TokenType::BangEqual),
+                        )),
+                        None => {
+                            let msg = format!("'{}' moet wellus of nietus geven.", name);
+                            Err(RuntimeErr::Err(op.line, msg))
+                        }
+                    };
+                }
+                None if op.kind != TokenType::EqualEqual && op.kind != TokenType::BangEqual => {
+                    let msg = format!(
+                        "'{}' heeft geen methode '{}' voor '{}'.",
+                        instance.class_name(),
+                        name,
+                        op.lexeme
+                    );
+                    return Err(RuntimeErr::Err(op.line, msg));
+                }
+                None => (),
+            }
+        }
+
+        // two integers stay integers and grow into a BigInt once they don't fit in 64 bits,
+        // any float in the mix promotes both sides to floats
         macro_rules! apply_arith_to_nums {
-            ($type: ident, $op: tt) => {
-                if let (Value::Num(num1), Value::Num(num2)) = (left, right) {
-                    Ok(Value::Num(num1 $op num2))
-                } else {
-                    let msg = concat!(stringify!($op), " kan alleen worden gebruikt op nummers.");
-                    Err(RuntimeErr::Err(op.line, msg.to_string()))
+            ($int_op: expr, $big_op: expr, $num_op: expr) => {{
+                if let (Value::Int(int1), Value::Int(int2)) = (&left, &right) {
+                    if let Some(int) = $int_op(*int1, *int2) {
+                        return Ok(Value::Int(int));
+                    }
+                }
+
+                match (left.as_big(), right.as_big()) {
+                    (Some(big1), Some(big2)) => match $big_op(&big1, &big2) {
+                        Some(big) => Ok(Value::from_big(big)),
+                        None => Err(division_by_zero_err(op)),
+                    },
+                    _ => match (left.as_num(), right.as_num()) {
+                        (Some(num1), Some(num2)) => Ok(Value::Num($num_op(num1, num2))),
+                        _ => {
+                            let msg =
+                                format!("'{}' kan alleen worden gebruikt op nummers.", op.lexeme);
+                            Err(RuntimeErr::Err(op.line, msg))
+                        }
+                    },
+                }
+            }};
+        }
+
+        macro_rules! apply_comparison {
+            ($op: tt) => {
+                match (&left, &right) {
+                    (Value::Int(int1), Value::Int(int2)) => Ok(Value::from_bool(int1 $op int2)),
+                    _ => match (left.as_big(), right.as_big()) {
+                        (Some(big1), Some(big2)) => Ok(Value::from_bool(big1 $op big2)),
+                        _ => match (left.as_num(), right.as_num()) {
+                            // like any comparison with NaN these are nietus
+                            (Some(num1), Some(num2)) if num1.is_nan() || num2.is_nan() => {
+                                Ok(Value::False)
+                            }
+                            // an integer and a float are compared exactly
+                            (Some(_), Some(_)) => {
+                                Ok(Value::from_bool(left.compare(&right) $op Ordering::Equal))
+                            }
+                            _ => match (&left, &right) {
+                                (Value::Str(_), Value::Str(_)) | (Value::List(_), Value::List(_)) => {
+                                    Ok(Value::from_bool(left.compare(&right) $op Ordering::Equal))
+                                }
+                                _ => {
+                                    let msg = concat!("'", stringify!($op), "' kan alleen worden gebruikt op twee getallen, twee reeksen of twee lijsten.");
+                                    Err(RuntimeErr::Err(op.line, msg.to_string()))
+                                }
+                            },
+                        },
+                    },
                 }
             };
         }
 
-        macro_rules! apply_logic_to_nums {
-            ($type: ident, $op: tt) => {
-                if let (Value::Num(num1), Value::Num(num2)) = (left, right) {
-                    Ok(Value::from_bool(num1 $op num2))
-                } else {
-                    let msg = concat!(stringify!($op), " kan alleen worden gebruikt op nummers.");
-                    Err(RuntimeErr::Err(op.line, msg.to_string()))
+        macro_rules! apply_bitwise_to_ints {
+            ($op: tt) => {
+                match (&left, &right) {
+                    (Value::Int(int1), Value::Int(int2)) => Ok(Value::Int(int1 $op int2)),
+                    _ => match (left.as_big(), right.as_big()) {
+                        (Some(big1), Some(big2)) => {
+                            Ok(Value::from_big(big1.bitwise(&big2, |a, b| a $op b)))
+                        }
+                        _ => {
+                            let msg = concat!("'", stringify!($op), "' kan alleen worden gebruikt op gehele getallen.");
+                            Err(RuntimeErr::Err(op.line, msg.to_string()))
+                        }
+                    },
                 }
             };
         }
 
         match op.kind {
-            TokenType::Plus => match (left, right) {
-                (Value::Num(num), Value::Str(str)) => {
-                    return Ok(Value::Str(format!("{}{}", num, str)))
-                }
-                (Value::Str(str), Value::Num(num)) => {
-                    return Ok(Value::Str(format!("{}{}", str, num)))
+            TokenType::Plus => match (&left, &right) {
+                (Value::Int(_) | Value::Big(_) | Value::Num(_), Value::Str(str)) => {
+                    Ok(Value::Str(format!("{}{}", left, str)))
                 }
-                (Value::Num(num1), Value::Num(num2)) => return Ok(Value::Num(num1 + num2)),
-                (Value::Str(str1), Value::Str(str2)) => {
-                    return Ok(Value::Str(format!("{}{}", str1, str2)))
+                (Value::Str(str), Value::Int(_) | Value::Big(_) | Value::Num(_)) => {
+                    Ok(Value::Str(format!("{}{}", str, right)))
                 }
+                (Value::Str(str1), Value::Str(str2)) => Ok(Value::Str(format!("{}{}", str1, str2))),
+                (
+                    Value::Int(_) | Value::Big(_) | Value::Num(_),
+                    Value::Int(_) | Value::Big(_) | Value::Num(_),
+                ) => apply_arith_to_nums!(
+                    i64::checked_add,
+                    |a: &BigInt, b: &BigInt| Some(a + b),
+                    |a, b| a + b
+                ),
 
                 _ => Err(RuntimeErr::Err(
                     op.line,
                     "'+' kan alleen worden gebruikt op nummers en strings.".to_string(),
                 )),
             },
-            TokenType::Minus => apply_arith_to_nums!(Minus, -),
-            TokenType::Star => apply_arith_to_nums!(Star, *),
-            TokenType::Slash => apply_arith_to_nums!(Slash, /),
-
-            TokenType::Caret => match (left, right) {
-                (Value::Num(num1), Value::Num(num2)) => {
-                    //if num2 < 0 {
-                    //   num2 *= -1;
-                    //}
-                    Ok(Value::Num(num1.powf(num2)))
-                }
+            TokenType::Minus => apply_arith_to_nums!(
+                i64::checked_sub,
+                |a: &BigInt, b: &BigInt| Some(a - b),
+                |a, b| a - b
+            ),
+            TokenType::Star => apply_arith_to_nums!(
+                i64::checked_mul,
+                |a: &BigInt, b: &BigInt| Some(a * b),
+                |a, b| a * b
+            ),
+            TokenType::Percent => apply_arith_to_nums!(
+                floor_mod,
+                |a: &BigInt, b: &BigInt| a.div_rem_floor(b).map(|(_, rem)| rem),
+                |a: f64, b: f64| a - b * (a / b).floor()
+            ),
+            TokenType::Backslash => apply_arith_to_nums!(
+                floor_div,
+                |a: &BigInt, b: &BigInt| a.div_rem_floor(b).map(|(div, _)| div),
+                |a: f64, b: f64| (a / b).floor()
+            ),
+
+            // dividing two integers gives a float, use '\' for integer division
+            TokenType::Slash => match (left.as_num(), right.as_num()) {
+                (Some(num1), Some(num2)) => Ok(Value::Num(num1 / num2)),
                 _ => Err(RuntimeErr::Err(
                     op.line,
-                    "'^' kan alleen worden gebruikt op nummers.".to_string(),
+                    "'/' kan alleen worden gebruikt op nummers.".to_string(),
                 )),
             },
 
-            TokenType::Greater => apply_logic_to_nums!(Greater, >),
-            TokenType::GreaterEqual => apply_logic_to_nums!(GreaterEqual, >=),
-            TokenType::Less => apply_logic_to_nums!(Less, <),
-            TokenType::LessEqual => apply_logic_to_nums!(LessEqaul, <=),
-            TokenType::Equal => apply_logic_to_nums!(Equal, ==),
+            TokenType::Caret => match (left.as_big(), right.as_big()) {
+                // a negative exponent can't give an integer
+                (Some(base), Some(exp)) if !exp.is_negative() => {
+                    let Some(exp) = exp.to_i64().and_then(|exp| u32::try_from(exp).ok()) else {
+                        return Err(exponent_err(op));
+                    };
+                    if let Value::Int(base) = left {
+                        if let Some(int) = base.checked_pow(exp) {
+                            return Ok(Value::Int(int));
+                        }
+                    }
+                    self.usage
+                        .reserve_bits(&self.limits, base.bit_len() * exp as u64)
+                        .map_err(|msg| limit_err(Some(op.line), msg))?;
+                    Ok(Value::from_big(base.pow(exp)))
+                }
+                _ => match (left.as_num(), right.as_num()) {
+                    (Some(num1), Some(num2)) => Ok(Value::Num(num1.powf(num2))),
+                    _ => Err(RuntimeErr::Err(
+                        op.line,
+                        "'^' kan alleen worden gebruikt op nummers.".to_string(),
+                    )),
+                },
+            },
+
+            TokenType::Ampersand => apply_bitwise_to_ints!(&),
+            TokenType::Pipe => apply_bitwise_to_ints!(|),
+            TokenType::Tilde => apply_bitwise_to_ints!(^),
+            TokenType::LessLess | TokenType::GreaterGreater => {
+                if let (TokenType::LessLess, Some(int), Value::Int(amount)) =
+                    (op.kind, left.as_big(), &right)
+                {
+                    if !int.is_zero() && *amount > 0 {
+                        self.usage
+                            .reserve_bits(&self.limits, int.bit_len() + *amount as u64)
+                            .map_err(|msg| limit_err(Some(op.line), msg))?;
+                    }
+                }
+                shift(&left, op, &right)
+            }
+
+            TokenType::Greater => apply_comparison!(>),
+            TokenType::GreaterEqual => apply_comparison!(>=),
+            TokenType::Less => apply_comparison!(<),
+            TokenType::LessEqual => apply_comparison!(<=),
 
             TokenType::EqualEqual => Ok(Value::from_bool(Value::is_equal(&left, &right))),
             TokenType::BangEqual => Ok(Value::from_bool(!Value::is_equal(&left, &right))),
@@ -417,7 +1046,7 @@ impl Interpreter {
                     let right = self.evaluate_expr(right)?.is_true();
 
                     if let Some(right) = right {
-                        return Ok(Value::from_bool(left && right));
+                        Ok(Value::from_bool(left && right))
                     } else {
                         let msg =
                             "'en' kan alleen worden gebruikt op waardigheids waarden.".to_string();
@@ -449,16 +1078,22 @@ impl Interpreter {
                     None => {
                         let msg =
                             "'of' kan alleen worden gebruikt op waardigheids waarden.".to_string();
-                        return Err(RuntimeErr::Err(op.line, msg));
+                        Err(RuntimeErr::Err(op.line, msg))
                     }
                 }
             }
+
+            // only evaluates the right side when the left side is niks
+            TokenType::QuestionQuestion => match self.evaluate_expr(left)? {
+                Value::Nil => self.evaluate_expr(right),
+                left => Ok(left),
+            },
             _ => panic!("Unreachable."),
         }
     }
 
     fn evaluate_var_expr(&mut self, token: &Token) -> Result<Value, RuntimeErr> {
-        match self.env.get_value(&token) {
+        match self.env.get_value(token) {
             Some(value) => Ok(value),
             None => Err(RuntimeErr::Err(
                 token.line,
@@ -473,3 +1108,214 @@ impl Interpreter {
         Ok(Value::Nil)
     }
 }
+
+fn limit_err(line: Option<usize>, msg: &str) -> RuntimeErr {
+    RuntimeErr::Limit(line.unwrap_or(0), msg.to_string())
+}
+
+fn check_arity(
+    callee: &dyn Callable,
+    arguments: &[Value],
+    right_paren: &Token,
+) -> Result<(), RuntimeErr> {
+    if callee.arity() != arguments.len() {
+        let msg = format!(
+            "Verwachtte {} argumenten maar kreeg er {}.",
+            callee.arity(),
+            arguments.len(),
+        );
+        return Err(RuntimeErr::Err(right_paren.line, msg));
+    }
+    Ok(())
+}
+
+// the variable a path like 'l[0].p' starts at, if it starts at one
+fn target_root(mut target: &Expr) -> Option<&Token> {
+    loop {
+        match target {
+            Expr::Var(name) => return Some(name),
+            Expr::Element { var, .. } => target = var,
+            Expr::Get { object, .. } => target = object,
+            _ => return None,
+        }
+    }
+}
+
+fn instance<'a>(object: &'a Value, name: &Token) -> Result<&'a Instance, RuntimeErr> {
+    match object {
+        Value::Instance(instance) => Ok(instance),
+        _ => {
+            let msg = format!(
+                "Alleen instanties hebben eigenschappen, '{}' heeft geen '{}'.",
+                object, name.lexeme
+            );
+            Err(RuntimeErr::Err(name.line, msg))
+        }
+    }
+}
+
+// negative indices count from the end of the list, so -1 is the last element
+fn list_index(index: &Value, len: usize, right_bracket: &Token) -> Result<usize, RuntimeErr> {
+    let position = match index {
+        Value::Int(int) if *int < 0 => int.checked_add(len as i64),
+        Value::Int(int) => Some(*int),
+        Value::Big(_) => None,
+        _ => return Err(index_type_err(index, right_bracket)),
+    };
+
+    match position {
+        Some(position) if position >= 0 && (position as usize) < len => Ok(position as usize),
+        _ => {
+            let msg = format!("Index {} valt buiten een lijst met lengte {}.", index, len);
+            Err(RuntimeErr::Err(right_bracket.line, msg))
+        }
+    }
+}
+
+// like an index, but bounds outside of the list are clamped to its start or end
+fn slice_bound(bound: &Value, len: usize, right_bracket: &Token) -> Result<usize, RuntimeErr> {
+    let len = len as i64;
+    let position = match bound {
+        Value::Int(int) if *int < 0 => int.saturating_add(len),
+        Value::Int(int) => *int,
+        Value::Big(big) if big.is_negative() => 0,
+        Value::Big(_) => len,
+        _ => return Err(index_type_err(bound, right_bracket)),
+    };
+    Ok(position.clamp(0, len) as usize)
+}
+
+fn index_type_err(index: &Value, right_bracket: &Token) -> RuntimeErr {
+    let msg = format!(
+        "Een index moet een geheel getal zijn, '{}' is dat niet.",
+        index
+    );
+    RuntimeErr::Err(right_bracket.line, msg)
+}
+
+fn set_element(
+    list: &mut Value,
+    indices: &[(Value, &Token)],
+    value: Value,
+) -> Result<(), RuntimeErr> {
+    let Some(((index, right_bracket), rest)) = indices.split_first() else {
+        *list = value;
+        return Ok(());
+    };
+
+    match list {
+        Value::List(elements) => {
+            let index = list_index(index, elements.len(), right_bracket)?;
+            set_element(&mut elements[index], rest, value)
+        }
+        _ => Err(RuntimeErr::Err(
+            right_bracket.line,
+            "Variabele is geen lijst.".to_string(),
+        )),
+    }
+}
+
+// pairs every target in the pattern with the part of the value it should get
+fn unpack<'a>(
+    pattern: &'a Pattern,
+    value: Value,
+    targets: &mut Vec<(&'a Expr, Value)>,
+) -> Result<(), RuntimeErr> {
+    match pattern {
+        Pattern::Target(target) => targets.push((target, value)),
+        Pattern::List { elements, token } => {
+            let Value::List(values) = value else {
+                let msg = format!(
+                    "Je kan alleen lijsten uitpakken, '{}' is geen lijst.",
+                    value
+                );
+                return Err(RuntimeErr::Err(token.line, msg));
+            };
+            if values.len() != elements.len() {
+                let msg = format!(
+                    "Verwachtte {} waarden om uit te pakken maar kreeg er {}.",
+                    elements.len(),
+                    values.len()
+                );
+                return Err(RuntimeErr::Err(token.line, msg));
+            }
+
+            for (element, value) in elements.iter().zip(values) {
+                unpack(element, value, targets)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn exponent_err(op: &Token) -> RuntimeErr {
+    let msg = format!("Het rechter getal van '{}' is te groot.", op.lexeme);
+    RuntimeErr::Err(op.line, msg)
+}
+
+// shifting left multiplies by 2^n, shifting right divides by 2^n rounding towards negative infinity
+fn shift(left: &Value, op: &Token, right: &Value) -> Result<Value, RuntimeErr> {
+    let Some(int) = left.as_big() else {
+        let msg = format!(
+            "'{}' kan alleen worden gebruikt op gehele getallen.",
+            op.lexeme
+        );
+        return Err(RuntimeErr::Err(op.line, msg));
+    };
+    let amount = match right {
+        Value::Int(amount) if *amount >= 0 => u32::try_from(*amount).ok(),
+        Value::Big(amount) if !amount.is_negative() => None,
+        _ => {
+            let msg = format!("'{}' verwacht een positief geheel getal.", op.lexeme);
+            return Err(RuntimeErr::Err(op.line, msg));
+        }
+    };
+
+    if let (Value::Int(int), Some(amount)) = (left, amount) {
+        if op.kind == TokenType::GreaterGreater {
+            return Ok(Value::Int(int >> amount.min(63)));
+        } else if amount < 64 && (int << amount) >> amount == *int {
+            return Ok(Value::Int(int << amount));
+        }
+    }
+
+    match (op.kind, amount) {
+        (TokenType::LessLess, Some(amount)) => {
+            Ok(Value::from_big(&int * &BigInt::from(2).pow(amount)))
+        }
+        (TokenType::LessLess, None) => Err(exponent_err(op)),
+        // shifting right by more than the number of bits leaves only the sign
+        (_, Some(amount)) if (amount as u64) < int.bit_len() => {
+            let (div, _) = int.div_rem_floor(&BigInt::from(2).pow(amount)).unwrap();
+            Ok(Value::from_big(div))
+        }
+        _ => Ok(Value::Int(if int.is_negative() { -1 } else { 0 })),
+    }
+}
+
+fn division_by_zero_err(op: &Token) -> RuntimeErr {
+    RuntimeErr::Err(op.line, "Je kan niet door nul delen.".to_string())
+}
+
+// integer division rounds towards negative infinity, so that
+// `a == (a \ b) * b + a % b` holds for negative numbers as well
+fn floor_div(a: i64, b: i64) -> Option<i64> {
+    let quotient = a.checked_div(b)?;
+    if a.wrapping_rem(b) != 0 && (a < 0) != (b < 0) {
+        Some(quotient - 1)
+    } else {
+        Some(quotient)
+    }
+}
+
+fn floor_mod(a: i64, b: i64) -> Option<i64> {
+    if b == 0 {
+        return None;
+    }
+    let remainder = a.wrapping_rem(b);
+    if remainder != 0 && (remainder < 0) != (b < 0) {
+        Some(remainder + b)
+    } else {
+        Some(remainder)
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    cell::RefCell,
+    fmt::Display,
+    fs::File,
+    io::{BufRead, BufReader, Lines},
+    rc::Rc,
+};
+
+use crate::{error::RuntimeErr, interpreter::Interpreter, value::Value};
+
+// anything that hands out values one at a time, such as a generator or the lines of a file.
+// Returns None once there are no values left
+pub trait Iterate: std::fmt::Debug {
+    fn next(&mut self, interpreter: &mut Interpreter) -> Result<Option<Value>, RuntimeErr>;
+    fn to_string(&self) -> String;
+}
+
+// iterators are shared rather than copied, so every copy of the value continues
+// where the others left off
+#[derive(Debug, Clone)]
+pub struct RoxIterator(Rc<RefCell<Box<dyn Iterate>>>);
+impl RoxIterator {
+    pub fn new(iterator: impl Iterate + 'static) -> Self {
+        Self(Rc::new(RefCell::new(Box::new(iterator))))
+    }
+
+    pub fn next(
+        &self,
+        line: usize,
+        interpreter: &mut Interpreter,
+    ) -> Result<Option<Value>, RuntimeErr> {
+        // a generator runs its function a bit further
+        interpreter.check_call_depth(line)?;
+
+        match self.0.try_borrow_mut() {
+            Ok(mut iterator) => iterator.next(interpreter),
+            Err(_) => Err(RuntimeErr::Err(
+                line,
+                "Een generator kan zichzelf niet vervolgen.".to_string(),
+            )),
+        }
+    }
+
+    // identifies the iterator, copies of the same iterator have the same id
+    pub fn id(&self) -> usize {
+        Rc::as_ptr(&self.0) as *const () as usize
+    }
+}
+impl Display for RoxIterator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.try_borrow() {
+            Ok(iterator) => write!(f, "{}", iterator.to_string()),
+            // a generator that is running asks for its own name
+            Err(_) => write!(f, "generator"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ListIter {
+    elements: std::vec::IntoIter<Value>,
+}
+impl ListIter {
+    pub fn new(elements: Vec<Value>) -> Self {
+        Self {
+            elements: elements.into_iter(),
+        }
+    }
+}
+impl Iterate for ListIter {
+    fn next(&mut self, _: &mut Interpreter) -> Result<Option<Value>, RuntimeErr> {
+        Ok(self.elements.next())
+    }
+
+    fn to_string(&self) -> String {
+        "iterator over lijst".to_string()
+    }
+}
+
+// the characters of a string, each as a string of its own
+#[derive(Debug)]
+pub struct StrIter {
+    chars: std::vec::IntoIter<char>,
+}
+impl StrIter {
+    pub fn new(str: &str) -> Self {
+        let chars: Vec<char> = str.chars().collect();
+        Self {
+            chars: chars.into_iter(),
+        }
+    }
+}
+impl Iterate for StrIter {
+    fn next(&mut self, _: &mut Interpreter) -> Result<Option<Value>, RuntimeErr> {
+        Ok(self.chars.next().map(|c| Value::Str(c.to_string())))
+    }
+
+    fn to_string(&self) -> String {
+        "iterator over reeks".to_string()
+    }
+}
+
+// the numbers that 'voor i van start tot end' goes through, counting down if end < start
+#[derive(Debug)]
+pub enum Range {
+    Int { current: i64, end: i64, step: i64 },
+    Num { current: f64, end: f64, step: f64 },
+}
+impl Range {
+    // returns None if the bounds aren't numbers
+    pub fn new(start: &Value, end: &Value) -> Option<Self> {
+        match (start, end) {
+            (Value::Int(start), Value::Int(end)) => Some(Range::Int {
+                current: *start,
+                end: *end,
+                step: if start < end { 1 } else { -1 },
+            }),
+            _ => match (start.as_num(), end.as_num()) {
+                (Some(start), Some(end)) => Some(Range::Num {
+                    current: start,
+                    end,
+                    step: if start < end { 1. } else { -1. },
+                }),
+                _ => None,
+            },
+        }
+    }
+}
+impl Iterate for Range {
+    fn next(&mut self, _: &mut Interpreter) -> Result<Option<Value>, RuntimeErr> {
+        match self {
+            Range::Int { current, end, step } => {
+                if (*step > 0 && current < end) || (*step < 0 && current > end) {
+                    let value = *current;
+                    *current = current.checked_add(*step).unwrap_or(*end);
+                    Ok(Some(Value::Int(value)))
+                } else {
+                    Ok(None)
+                }
+            }
+            Range::Num { current, end, step } => {
+                if (*step > 0. && current < end) || (*step < 0. && current > end) {
+                    let value = *current;
+                    *current += *step;
+                    Ok(Some(Value::Num(value)))
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "bereik".to_string()
+    }
+}
+
+// reads a file one line at a time, so it never has to be in memory all at once
+#[derive(Debug)]
+pub struct LinesIter {
+    path: String,
+    lines: Lines<BufReader<File>>,
+}
+impl LinesIter {
+    pub fn open(path: &str) -> Result<Self, RuntimeErr> {
+        match File::open(path) {
+            Ok(file) => Ok(Self {
+                path: path.to_string(),
+                lines: BufReader::new(file).lines(),
+            }),
+            Err(err) => Err(RuntimeErr::Err(
+                0,
+                format!("Kon '{}' niet openen: {}.", path, err),
+            )),
+        }
+    }
+}
+impl Iterate for LinesIter {
+    fn next(&mut self, _: &mut Interpreter) -> Result<Option<Value>, RuntimeErr> {
+        match self.lines.next() {
+            Some(Ok(line)) => Ok(Some(Value::Str(line))),
+            Some(Err(err)) => Err(RuntimeErr::Err(
+                0,
+                format!("Kon '{}' niet lezen: {}.", self.path, err),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn to_string(&self) -> String {
+        format!("regels {}", self.path)
+    }
+}
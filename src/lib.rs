@@ -0,0 +1,51 @@
+//! Rox, a Dutch flavoured Lox.
+//!
+//! A host program runs Rox through an [`Engine`]:
+//!
+//! ```
+//! use lox::{Engine, Value};
+//!
+//! let mut engine = Engine::new();
+//! engine.set_global("basis", Value::Int(40));
+//! engine.eval("laat antwoord = basis + 2;").unwrap();
+//!
+//! let antwoord = engine.get_global("antwoord").unwrap();
+//! assert_eq!(antwoord.to_string(), "42");
+//! ```
+
+mod bigint;
+mod callable;
+mod checker;
+mod class;
+mod engine;
+mod environment;
+mod error;
+mod expr;
+mod formatter;
+mod generator;
+#[cfg(test)]
+mod golden;
+mod interpreter;
+mod iterator;
+mod limits;
+mod linter;
+mod native;
+mod output;
+mod parser;
+mod permissions;
+mod resolver;
+mod rox;
+mod scanner;
+mod stmt;
+mod token;
+mod token_type;
+mod value;
+
+pub use engine::Engine;
+pub use error::{Diagnostic, EvalError};
+pub use limits::Limits;
+pub use native::{FromRox, IntoRox};
+pub use output::Buffer;
+pub use permissions::{Capability, Permissions};
+pub use rox::{Rox, STACK_SIZE};
+pub use value::Value;
@@ -0,0 +1,243 @@
+use std::time::{Duration, Instant};
+
+use crate::value::Value;
+
+// how often the clock is read, reading it on every step would slow everything down
+const STEPS_PER_CLOCK_CHECK: u64 = 1024;
+
+// limits on what a program may use, so a host can run programs it doesn't trust.
+// A program that goes over a limit is stopped, nothing in Rox can catch that
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    // the number of statements and expressions that may be evaluated
+    pub max_steps: Option<u64>,
+    pub timeout: Option<Duration>,
+    // the total size of all lists, strings and big integers that are made: elements, counting
+    // those of nested lists, and bytes. Reading a variable copies its value, so every read of
+    // a list or string counts, but 'l[i]' only copies the element
+    pub max_allocation: Option<usize>,
+}
+
+// what a program has used of its limits so far
+#[derive(Debug, Default)]
+pub struct Usage {
+    steps: u64,
+    allocated: usize,
+    deadline: Option<Instant>,
+}
+impl Usage {
+    pub fn start(limits: &Limits) -> Self {
+        Self {
+            steps: 0,
+            allocated: 0,
+            deadline: limits.timeout.map(|timeout| Instant::now() + timeout),
+        }
+    }
+
+    // returns the error message if a limit is reached
+    pub fn step(&mut self, limits: &Limits) -> Result<(), &'static str> {
+        self.steps += 1;
+        if limits.max_steps.is_some_and(|max| self.steps > max) {
+            return Err("Het programma heeft te veel stappen gezet.");
+        }
+
+        if let Some(deadline) = self.deadline {
+            if self.steps.is_multiple_of(STEPS_PER_CLOCK_CHECK) && Instant::now() > deadline {
+                return Err("Het programma duurde te lang.");
+            }
+        }
+        Ok(())
+    }
+
+    // 'amount' is a number of elements or bytes, see 'size'
+    pub fn allocate(&mut self, limits: &Limits, amount: usize) -> Result<(), &'static str> {
+        let Some(max) = limits.max_allocation else {
+            return Ok(());
+        };
+
+        self.allocated += amount;
+        if self.allocated > max {
+            return Err(ALLOCATION_MSG);
+        }
+        Ok(())
+    }
+
+    // some integers take longer to compute than any timeout allows, so an operation that
+    // would make one of about this many bits is refused before it starts
+    pub fn reserve_bits(&self, limits: &Limits, bits: u64) -> Result<(), &'static str> {
+        let Some(max) = limits.max_allocation else {
+            return Ok(());
+        };
+
+        if (self.allocated as u64).saturating_add(bits / 8) > max as u64 {
+            return Err(ALLOCATION_MSG);
+        }
+        Ok(())
+    }
+}
+
+const ALLOCATION_MSG: &str = "Het programma heeft te veel geheugen gebruikt.";
+
+// copying a list copies the lists in it too
+pub fn size(value: &Value) -> usize {
+    match value {
+        Value::Str(str) => str.len(),
+        Value::List(values) => values.len() + values.iter().map(size).sum::<usize>(),
+        Value::Big(big) => (big.bit_len() / 8) as usize,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{engine::Engine, error::EvalError};
+
+    fn limit_message(engine: &mut Engine, source: &str) -> String {
+        match engine.eval(source) {
+            Err(EvalError::Limit(diagnostic)) => diagnostic.message,
+            _ => panic!("Expected a limit error."),
+        }
+    }
+
+    #[test]
+    fn steps() {
+        let mut engine = Engine::new();
+        engine.set_limits(Limits {
+            max_steps: Some(1000),
+            ..Limits::default()
+        });
+
+        let source = "laat i = 0; terwijl i < 10 i = i + 1; geef i;";
+        assert_eq!(engine.eval(source).unwrap().to_string(), "10");
+        // the budget starts over for every eval
+        let source = "i = 0; terwijl i < 10 i = i + 1; geef i;";
+        assert_eq!(engine.eval(source).unwrap().to_string(), "10");
+
+        let msg = limit_message(&mut engine, "terwijl wellus {}");
+        assert_eq!(msg, "Het programma heeft te veel stappen gezet.");
+
+        let source = "
+            proces lus() { terwijl wellus {} }
+            voor x in [1, 2] lus();";
+        let msg = limit_message(&mut engine, source);
+        assert_eq!(msg, "Het programma heeft te veel stappen gezet.");
+
+        // a tail call doesn't go deeper, so only the steps stop this
+        let msg = limit_message(&mut engine, "proces f(n) { geef f(n + 1); } f(0);");
+        assert_eq!(msg, "Het programma heeft te veel stappen gezet.");
+    }
+
+    #[test]
+    fn timeout() {
+        let mut engine = Engine::new();
+        engine.set_limits(Limits {
+            timeout: Some(Duration::from_millis(50)),
+            ..Limits::default()
+        });
+
+        let msg = limit_message(&mut engine, "terwijl wellus {}");
+        assert_eq!(msg, "Het programma duurde te lang.");
+    }
+
+    #[test]
+    fn allocation() {
+        let mut engine = Engine::new();
+        engine.set_limits(Limits {
+            max_allocation: Some(10_000),
+            ..Limits::default()
+        });
+
+        let source = "laat s = \"\"; voor i van 0 tot 10 s = s + \"x\"; geef s;";
+        assert_eq!(engine.eval(source).unwrap().to_string(), "xxxxxxxxxx");
+
+        let source = "s = \"\"; terwijl wellus s = s + \"x\";";
+        let msg = limit_message(&mut engine, source);
+        assert_eq!(msg, "Het programma heeft te veel geheugen gebruikt.");
+
+        // every element of a nested list counts, not just the length of the outer list
+        let source = "
+            laat l = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+            laat j = 0;
+            terwijl j < 30 { l = [l, l]; j = j + 1; }";
+        let msg = limit_message(&mut engine, source);
+        assert_eq!(msg, "Het programma heeft te veel geheugen gebruikt.");
+    }
+
+    // a value is counted where it is made or copied, not again by every expression around
+    // it, and 'l[i]' doesn't copy the list
+    #[test]
+    fn linear_pass() {
+        let mut engine = Engine::new();
+        engine.set_limits(Limits {
+            max_allocation: Some(1_000_000),
+            ..Limits::default()
+        });
+
+        let source = "
+            proces getallen(n) { voor i van 0 tot n lever i; }
+            laat l = verzamel(getallen(2000));
+            laat s = 0;
+            voor i van 0 tot 2000 s = s + l[i];
+            geef s;";
+        assert_eq!(engine.eval(source).unwrap().to_string(), "1999000");
+    }
+
+    // huge integers are rejected before they are made, and the loops in 'fact' and 'fib'
+    // count as steps
+    #[test]
+    fn big_integers() {
+        let mut engine = Engine::new();
+        engine.set_limits(Limits {
+            max_steps: Some(100_000),
+            max_allocation: Some(100_000),
+            timeout: Some(Duration::from_secs(5)),
+        });
+
+        assert_eq!(
+            engine.eval("geef 2 ^ 100 > 0;").unwrap().to_string(),
+            "wellus"
+        );
+        for source in [
+            "geef 2 ^ 50000000;",
+            "geef 3 ^ 4000000000;",
+            "geef 1 << 50000000;",
+            "geef fact(100000000);",
+            "geef fib(100000000);",
+        ] {
+            let msg = limit_message(&mut engine, source);
+            assert_eq!(
+                msg, "Het programma heeft te veel geheugen gebruikt.",
+                "{source}"
+            );
+        }
+
+        engine.set_limits(Limits {
+            max_steps: Some(1000),
+            ..Limits::default()
+        });
+        let msg = limit_message(&mut engine, "geef fib(5000);");
+        assert_eq!(msg, "Het programma heeft te veel stappen gezet.");
+    }
+
+    // 'l[i] = x' changes the list in place, copying it for every element would go over
+    // the budget long before the loops are done
+    #[test]
+    fn element_assignment_in_place() {
+        let mut engine = Engine::new();
+        engine.set_limits(Limits {
+            max_allocation: Some(100_000),
+            ..Limits::default()
+        });
+
+        let source = "
+            proces nullen(n) { voor i van 0 tot n lever 0; }
+            laat l = verzamel(nullen(5000));
+            voor i van 0 tot 5000 l[i] = i;
+            klas Houder { nieuw() { dit.lijst = verzamel(nullen(5000)); } }
+            laat h = Houder();
+            voor i van 0 tot 5000 h.lijst[i] = i;
+            geef [l[4999], h.lijst[4999]];";
+        assert_eq!(engine.eval(source).unwrap().to_string(), "[4999, 4999]");
+    }
+}
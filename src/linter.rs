@@ -0,0 +1,543 @@
+use std::collections::HashMap;
+
+use crate::{
+    callable::FunDeclaration,
+    class::CONSTRUCTOR,
+    error::Diagnostic,
+    expr::Expr,
+    stmt::{If, Stmt},
+    token::{Literal, Token},
+};
+
+// static pass for 'rox check' that warns about code that is allowed, but probably
+// isn't what was meant. It runs after the resolver, so the names are known to be valid.
+//
+// It warns about:
+// - variables and parameters that are never used. Globals are left alone, because a
+//   host program can use them, and so are names that start with '_'
+// - statements after a 'geef', they never run. The warning is on the line of the 'geef'
+// - calls to a function or class with the wrong number of arguments, as long as
+//   nothing else is ever assigned to its name
+// - conditions that are a literal other than wellus or nietus, 'als' and 'terwijl'
+//   treat those as nietus
+pub struct Linter {
+    scopes: Vec<Scope>,
+    warnings: Vec<Diagnostic>,
+}
+
+#[derive(Default)]
+struct Scope {
+    variables: HashMap<String, Variable>,
+    // names used inside functions that weren't declared yet, they might be declared
+    // later on in one of the scopes around the function
+    pending: Vec<Use>,
+}
+
+struct Variable {
+    line: usize,
+    used: bool,
+    // whether to warn if it is never used
+    check_unused: bool,
+    // the number of arguments of a function or class, None once something is assigned to it
+    arity: Option<usize>,
+    calls: Vec<Call>,
+}
+
+struct Use {
+    name: String,
+    call: Option<Call>,
+}
+
+#[derive(Clone, Copy)]
+struct Call {
+    line: usize,
+    arguments: usize,
+}
+
+impl Linter {
+    // the globals that are already there, with the number of arguments of the functions
+    pub fn new(globals: HashMap<String, Option<usize>>) -> Self {
+        let mut global_scope = Scope::default();
+        for (name, arity) in globals {
+            let variable = Variable {
+                line: 0,
+                used: false,
+                check_unused: false,
+                arity,
+                calls: Vec::new(),
+            };
+            global_scope.variables.insert(name, variable);
+        }
+
+        Self {
+            scopes: vec![global_scope],
+            warnings: Vec::new(),
+        }
+    }
+
+    // the warnings, ordered by line and then by message, the variables of a scope are
+    // kept in a map so their own order can't be used
+    pub fn lint(mut self, statements: &[Stmt]) -> Vec<Diagnostic> {
+        self.lint_statements(statements);
+        let global_scope = self.scopes.pop().unwrap();
+        self.check_scope(global_scope);
+
+        self.warnings
+            .sort_by(|a, b| (a.line, &a.message).cmp(&(b.line, &b.message)));
+        self.warnings
+    }
+
+    fn warning(&mut self, line: usize, msg: String) {
+        self.warnings.push(Diagnostic::new(line, msg));
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(Scope::default());
+    }
+
+    fn end_scope(&mut self) {
+        let scope = self.scopes.pop().unwrap();
+        let pending = self.check_scope(scope);
+        self.scopes.last_mut().unwrap().pending.extend(pending);
+    }
+
+    // warns about the variables of a scope that is done, returns the uses of names
+    // that it doesn't declare
+    fn check_scope(&mut self, mut scope: Scope) -> Vec<Use> {
+        let mut unresolved = Vec::new();
+        for use_ in std::mem::take(&mut scope.pending) {
+            match scope.variables.get_mut(&use_.name) {
+                Some(variable) => variable.used(use_.call),
+                None => unresolved.push(use_),
+            }
+        }
+
+        for (name, variable) in scope.variables {
+            if variable.check_unused && !variable.used && !name.starts_with('_') {
+                let msg = format!("'{}' wordt nooit gebruikt.", name);
+                self.warning(variable.line, msg);
+            }
+
+            let Some(arity) = variable.arity else {
+                continue;
+            };
+            for call in variable.calls {
+                if call.arguments != arity {
+                    let msg = format!(
+                        "'{}' verwacht {} argumenten maar krijgt er {}.",
+                        name, arity, call.arguments
+                    );
+                    self.warning(call.line, msg);
+                }
+            }
+        }
+        unresolved
+    }
+
+    fn declare(&mut self, name: &Token, arity: Option<usize>) {
+        // globals are left alone, a host program can use them
+        let check_unused = self.scopes.len() > 1 && arity.is_none();
+        let variable = Variable {
+            line: name.line,
+            used: false,
+            check_unused,
+            arity,
+            calls: Vec::new(),
+        };
+        let scope = self.scopes.last_mut().unwrap();
+        scope.variables.insert(name.lexeme.clone(), variable);
+    }
+
+    fn use_name(&mut self, name: &Token, call: Option<Call>) {
+        let variable = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.variables.get_mut(&name.lexeme));
+        match variable {
+            Some(variable) => variable.used(call),
+            None => self.scopes.last_mut().unwrap().pending.push(Use {
+                name: name.lexeme.clone(),
+                call,
+            }),
+        }
+    }
+
+    // after an assignment the name might not be the function it was declared as
+    fn assign(&mut self, name: &Token) {
+        let variable = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.variables.get_mut(&name.lexeme));
+        if let Some(variable) = variable {
+            variable.arity = None;
+        }
+    }
+
+    fn lint_statements(&mut self, statements: &[Stmt]) {
+        // the line of the 'geef' that came before, if nothing has been warned about yet
+        let mut returned = None;
+        for stmt in statements {
+            if let Some(line) = returned.take() {
+                let msg = "De code na deze 'geef' wordt nooit uitgevoerd.";
+                self.warning(line, msg.to_string());
+            }
+            if let Stmt::Return { keyword, .. } = stmt {
+                returned = Some(keyword.line);
+            }
+            self.lint_stmt(stmt);
+        }
+    }
+
+    fn lint_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Expr(expr) | Stmt::Print(expr) | Stmt::Println(expr) => self.lint_expr(expr),
+            Stmt::Var { name, expr, .. } => {
+                self.lint_expr(expr);
+                self.declare(name, None);
+            }
+            Stmt::Destructure { pattern, expr, .. } => {
+                self.lint_expr(expr);
+                for target in pattern.targets() {
+                    if let Expr::Var(name) = target {
+                        self.declare(name, None);
+                    }
+                }
+            }
+            Stmt::Block(statements) => {
+                self.begin_scope();
+                self.lint_statements(statements);
+                self.end_scope();
+            }
+            Stmt::If {
+                first_if,
+                else_ifs,
+                final_else,
+            } => {
+                for If {
+                    keyword,
+                    should_execute,
+                    statement,
+                } in std::iter::once(first_if).chain(else_ifs)
+                {
+                    self.lint_condition(keyword, should_execute);
+                    self.lint_stmt(statement);
+                }
+                if let Some(final_else) = final_else {
+                    self.lint_stmt(final_else);
+                }
+            }
+            Stmt::While {
+                keyword,
+                condition,
+                body,
+            } => {
+                self.lint_condition(keyword, condition);
+                self.lint_stmt(body);
+            }
+            Stmt::For {
+                name,
+                start,
+                end,
+                body,
+            } => {
+                self.lint_expr(start);
+                self.lint_expr(end);
+                self.lint_loop(name, body);
+            }
+            Stmt::ForEach {
+                name,
+                iterable,
+                body,
+            } => {
+                self.lint_expr(iterable);
+                self.lint_loop(name, body);
+            }
+            Stmt::Function(function) => {
+                self.declare(&function.name, Some(function.params.len()));
+                self.lint_function(function);
+            }
+            Stmt::Class { name, methods } => {
+                let arity = methods
+                    .iter()
+                    .find(|method| method.name.lexeme == CONSTRUCTOR)
+                    .map_or(0, |constructor| constructor.params.len());
+                self.declare(name, Some(arity));
+                for method in methods {
+                    self.lint_function(method);
+                }
+            }
+            Stmt::Return { expr, .. } | Stmt::Yield { expr, .. } => self.lint_expr(expr),
+            Stmt::Test { body, .. } => {
+                self.begin_scope();
+                self.lint_statements(body);
+                self.end_scope();
+            }
+        }
+    }
+
+    fn lint_function(&mut self, function: &FunDeclaration) {
+        self.begin_scope();
+        for param in &function.params {
+            self.declare(param, None);
+        }
+        self.lint_statements(&function.body);
+        self.end_scope();
+    }
+
+    fn lint_loop(&mut self, name: &Token, body: &Stmt) {
+        self.begin_scope();
+        self.declare(name, None);
+        self.lint_stmt(body);
+        self.end_scope();
+    }
+
+    // the keyword is the 'als' or 'terwijl'
+    fn lint_condition(&mut self, keyword: &Token, condition: &Expr) {
+        let mut literal = condition;
+        while let Expr::Grouping(expr) = literal {
+            literal = expr;
+        }
+
+        let description = match literal {
+            Expr::Lit(Literal::Str(str)) => format!("\"{}\"", str),
+            Expr::Lit(Literal::Nil) => "niks".to_string(),
+            Expr::Lit(Literal::True | Literal::False | Literal::None) => String::new(),
+            Expr::Lit(number) => number.to_string(),
+            Expr::List(_) => "[...]".to_string(),
+            _ => String::new(),
+        };
+        if !description.is_empty() {
+            let msg = format!("De voorwaarde {} is geen waarheidswaarde.", description);
+            self.warning(keyword.line, msg);
+        }
+        self.lint_expr(condition);
+    }
+
+    fn lint_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Lit(_) | Expr::This(_) => (),
+            Expr::Var(name) => self.use_name(name, None),
+            Expr::Grouping(expr) | Expr::Unary(_, expr) => self.lint_expr(expr),
+            Expr::Binary(left, _, right) | Expr::Logic(left, _, right) => {
+                self.lint_expr(left);
+                self.lint_expr(right);
+            }
+            Expr::Conditional {
+                keyword,
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.lint_condition(keyword, condition);
+                self.lint_expr(then_branch);
+                self.lint_expr(else_branch);
+            }
+            Expr::Assign(name, value) => {
+                self.lint_expr(value);
+                self.assign(name);
+            }
+            Expr::AssignToElement {
+                var, index, value, ..
+            } => {
+                self.lint_expr(var);
+                self.lint_expr(index);
+                self.lint_expr(value);
+            }
+            Expr::Destructure { pattern, value } => {
+                self.lint_expr(value);
+                for target in pattern.targets() {
+                    match target {
+                        Expr::Var(name) => self.assign(name),
+                        target => self.lint_expr(target),
+                    }
+                }
+            }
+            Expr::Call(callee, paren, arguments) => {
+                match callee.as_ref() {
+                    Expr::Var(name) => {
+                        let call = Call {
+                            line: paren.line,
+                            arguments: arguments.len(),
+                        };
+                        self.use_name(name, Some(call));
+                    }
+                    callee => self.lint_expr(callee),
+                }
+                for argument in arguments {
+                    self.lint_expr(argument);
+                }
+            }
+            Expr::List(elements) => {
+                for element in elements {
+                    self.lint_expr(element);
+                }
+            }
+            Expr::Element { var, index, .. } => {
+                self.lint_expr(var);
+                self.lint_expr(index);
+            }
+            Expr::Slice {
+                var, start, end, ..
+            } => {
+                self.lint_expr(var);
+                for bound in [start, end].into_iter().flatten() {
+                    self.lint_expr(bound);
+                }
+            }
+            Expr::Get { object, .. } => self.lint_expr(object),
+            Expr::Set { object, value, .. } => {
+                self.lint_expr(object);
+                self.lint_expr(value);
+            }
+        }
+    }
+}
+
+impl Variable {
+    fn used(&mut self, call: Option<Call>) {
+        self.used = true;
+        self.calls.extend(call);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{engine::Engine, error::Diagnostic};
+
+    fn check(source: &str) -> Vec<Diagnostic> {
+        Engine::new().check(source).unwrap()
+    }
+
+    fn assert_warnings(source: &str, expected: &[(usize, &str)]) {
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(line, message)| Diagnostic::new(*line, *message))
+            .collect();
+        assert_eq!(check(source), expected, "{}", source);
+    }
+
+    #[test]
+    fn unused() {
+        let source = "
+            laat globaal = 1;
+            proces f(a, b, _c) {
+                laat x = 1;
+                laat y = 2;
+                y = 3;
+                proces g() {
+                    geef later;
+                }
+                laat later = a;
+                geef g;
+            }
+            voor i in [1, 2] roep \"hoi\";";
+        assert_warnings(
+            source,
+            &[
+                (3, "'b' wordt nooit gebruikt."),
+                (4, "'x' wordt nooit gebruikt."),
+                (5, "'y' wordt nooit gebruikt."),
+                (13, "'i' wordt nooit gebruikt."),
+            ],
+        );
+    }
+
+    #[test]
+    fn same_line() {
+        let source = "
+            proces f(d, c, b, a) { geef; }
+            f(1, 2, 3, 4);";
+        assert_warnings(
+            source,
+            &[
+                (2, "'a' wordt nooit gebruikt."),
+                (2, "'b' wordt nooit gebruikt."),
+                (2, "'c' wordt nooit gebruikt."),
+                (2, "'d' wordt nooit gebruikt."),
+            ],
+        );
+    }
+
+    #[test]
+    fn unreachable() {
+        let source = "
+            proces f(x) {
+                als x {
+                    geef 1;
+                    roep 2;
+                    roep 3;
+                }
+                geef 4;
+            }
+            f(wellus);
+            geef;
+            roep 5;";
+        assert_warnings(
+            source,
+            &[
+                (4, "De code na deze 'geef' wordt nooit uitgevoerd."),
+                (11, "De code na deze 'geef' wordt nooit uitgevoerd."),
+            ],
+        );
+    }
+
+    #[test]
+    fn arguments() {
+        let source = "
+            proces f(a, b) { geef a + b; }
+            klas Punt { nieuw(x, y) { dit.x = x; dit.y = y; } }
+            klas Leeg {}
+            f(1);
+            Punt(1, 2);
+            Punt(1);
+            Leeg(1);
+            lengte([1], 2);
+            laat g = f;
+            g(1);
+            proces h(x) { geef x; }
+            h = f;
+            h(1, 2);";
+        assert_warnings(
+            source,
+            &[
+                (5, "'f' verwacht 2 argumenten maar krijgt er 1."),
+                (7, "'Punt' verwacht 2 argumenten maar krijgt er 1."),
+                (8, "'Leeg' verwacht 0 argumenten maar krijgt er 1."),
+                (9, "'lengte' verwacht 1 argumenten maar krijgt er 2."),
+            ],
+        );
+    }
+
+    #[test]
+    fn conditions() {
+        let source = "
+            als 1 roep 1;
+            anders als (\"ja\") roep 2;
+            terwijl [] {}
+            terwijl niks {}
+            terwijl nietus {}
+            als 1 == 1 roep 3;";
+        assert_warnings(
+            source,
+            &[
+                (2, "De voorwaarde 1 is geen waarheidswaarde."),
+                (3, "De voorwaarde \"ja\" is geen waarheidswaarde."),
+                (4, "De voorwaarde [...] is geen waarheidswaarde."),
+                (5, "De voorwaarde niks is geen waarheidswaarde."),
+            ],
+        );
+    }
+
+    // the resolver already reports these before anything runs
+    #[test]
+    fn undeclared() {
+        let mut engine = Engine::new();
+        let errors = engine.check("onbekend = 1;").unwrap_err();
+        assert_eq!(
+            errors.diagnostics(),
+            [Diagnostic::new(1, "'onbekend' is een onbekende variabele.")]
+        );
+    }
+}
@@ -1,22 +1,26 @@
-use std::env;
+use std::{env, process, thread};
 
-use rox::Rox;
-
-mod callable;
-mod environment;
-mod error;
-mod expr;
-mod interpreter;
-mod parser;
-mod rox;
-mod scanner;
-mod stmt;
-mod token;
-mod token_type;
-mod value;
+use lox::{Rox, STACK_SIZE};
 
 fn main() {
+    let rox = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .unwrap();
+    if !rox.join().unwrap() {
+        process::exit(1);
+    }
+}
+
+// returns whether it succeeded
+fn run() -> bool {
     let mut lox = Rox::new();
+    if let Some(depth) = env::var("ROX_RECURSIEDIEPTE")
+        .ok()
+        .and_then(|depth| depth.parse().ok())
+    {
+        lox.set_max_call_depth(depth);
+    }
 
     let arguments: Vec<String> = env::args().collect();
     env::set_var("RUST_BACKTRACE", "1");
@@ -24,6 +28,23 @@ fn main() {
     if arguments.len() == 1 {
         // run lox code from a file
         lox.run_file("file.rox");
+    } else if arguments[1] == "test" {
+        // run the 'toets' blocks of a file or directory
+        let path = arguments.get(2).map_or(".", String::as_str);
+        return lox.run_tests(path);
+    } else if arguments[1] == "check" {
+        // look for mistakes in a file or directory without running it
+        let path = arguments.get(2).map_or(".", String::as_str);
+        return lox.check_files(path);
+    } else if arguments[1] == "fmt" {
+        // format a file or directory, '--check' only checks whether it is formatted
+        let check = arguments.iter().any(|arg| arg == "--check");
+        let path = arguments
+            .iter()
+            .skip(2)
+            .find(|arg| *arg != "--check")
+            .map_or(".", String::as_str);
+        return lox.format_files(path, check);
     } else {
         // run lox code from a prompt
         let mut input = String::new();
@@ -35,4 +56,5 @@ fn main() {
         println!("{}", input);
         lox.run_prompt(input);
     }
+    true
 }
@@ -0,0 +1,325 @@
+use std::{fmt::Debug, rc::Rc};
+
+use crate::{callable::Callable, error::RuntimeErr, interpreter::Interpreter, value::Value};
+
+// converts the argument of a native function from a Rox value.
+// Returns None if the value has the wrong type
+pub trait FromRox: Sized {
+    fn from_rox(value: &Value) -> Option<Self>;
+    // the type as it is named in the error message, e.g. 'getal'
+    fn type_name() -> String;
+}
+
+// converts the return value of a native function into a Rox value
+pub trait IntoRox {
+    fn into_rox(self) -> Value;
+}
+
+impl FromRox for Value {
+    fn from_rox(value: &Value) -> Option<Self> {
+        Some(value.clone())
+    }
+
+    fn type_name() -> String {
+        "waarde".to_string()
+    }
+}
+impl IntoRox for Value {
+    fn into_rox(self) -> Value {
+        self
+    }
+}
+
+impl FromRox for f64 {
+    fn from_rox(value: &Value) -> Option<Self> {
+        value.as_num()
+    }
+
+    fn type_name() -> String {
+        "getal".to_string()
+    }
+}
+impl IntoRox for f64 {
+    fn into_rox(self) -> Value {
+        Value::Num(self)
+    }
+}
+
+impl FromRox for i64 {
+    fn from_rox(value: &Value) -> Option<Self> {
+        match value {
+            Value::Int(int) => Some(*int),
+            _ => None,
+        }
+    }
+
+    fn type_name() -> String {
+        "geheel getal".to_string()
+    }
+}
+impl IntoRox for i64 {
+    fn into_rox(self) -> Value {
+        Value::Int(self)
+    }
+}
+
+impl FromRox for String {
+    fn from_rox(value: &Value) -> Option<Self> {
+        match value {
+            Value::Str(str) => Some(str.clone()),
+            _ => None,
+        }
+    }
+
+    fn type_name() -> String {
+        "reeks".to_string()
+    }
+}
+impl IntoRox for String {
+    fn into_rox(self) -> Value {
+        Value::Str(self)
+    }
+}
+impl IntoRox for &str {
+    fn into_rox(self) -> Value {
+        Value::Str(self.to_string())
+    }
+}
+
+impl FromRox for bool {
+    fn from_rox(value: &Value) -> Option<Self> {
+        match value {
+            Value::True => Some(true),
+            Value::False => Some(false),
+            _ => None,
+        }
+    }
+
+    fn type_name() -> String {
+        "waarheidswaarde".to_string()
+    }
+}
+impl IntoRox for bool {
+    fn into_rox(self) -> Value {
+        Value::from_bool(self)
+    }
+}
+
+impl<T: FromRox> FromRox for Vec<T> {
+    fn from_rox(value: &Value) -> Option<Self> {
+        match value {
+            Value::List(values) => values.iter().map(T::from_rox).collect(),
+            _ => None,
+        }
+    }
+
+    fn type_name() -> String {
+        format!("lijst van {}", T::type_name())
+    }
+}
+impl<T: IntoRox> IntoRox for Vec<T> {
+    fn into_rox(self) -> Value {
+        Value::List(self.into_iter().map(T::into_rox).collect())
+    }
+}
+
+// niks is None
+impl<T: FromRox> FromRox for Option<T> {
+    fn from_rox(value: &Value) -> Option<Self> {
+        match value {
+            Value::Nil => Some(None),
+            value => T::from_rox(value).map(Some),
+        }
+    }
+
+    fn type_name() -> String {
+        format!("{} of niks", T::type_name())
+    }
+}
+impl<T: IntoRox> IntoRox for Option<T> {
+    fn into_rox(self) -> Value {
+        match self {
+            Some(value) => value.into_rox(),
+            None => Value::Nil,
+        }
+    }
+}
+
+impl IntoRox for () {
+    fn into_rox(self) -> Value {
+        Value::Nil
+    }
+}
+
+// what a native function returns, an Err becomes a runtime error with the message
+pub trait NativeResult {
+    fn into_result(self) -> Result<Value, String>;
+}
+impl<T: IntoRox> NativeResult for T {
+    fn into_result(self) -> Result<Value, String> {
+        Ok(self.into_rox())
+    }
+}
+impl<T: IntoRox> NativeResult for Result<T, String> {
+    fn into_result(self) -> Result<Value, String> {
+        self.map(T::into_rox)
+    }
+}
+
+type Erased = dyn Fn(&str, Vec<Value>) -> Result<Value, RuntimeErr>;
+
+// a Rust closure that can be called from Rox, see 'Engine::register_fn'.
+// Args is the tuple of the argument types, which tells the closures with
+// a different number of arguments apart
+pub trait NativeFn<Args> {
+    fn arity(&self) -> usize;
+    fn erase(self) -> Rc<Erased>;
+}
+
+macro_rules! impl_native_fn {
+    ($($arg: ident $position: literal),*) => {
+        impl<F, R, $($arg),*> NativeFn<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R + 'static,
+            R: NativeResult,
+            $($arg: FromRox,)*
+        {
+            fn arity(&self) -> usize {
+                <[usize]>::len(&[$($position),*])
+            }
+
+            #[allow(non_snake_case, unused_variables, unused_mut)]
+            fn erase(self) -> Rc<Erased> {
+                Rc::new(move |name, arguments| {
+                    let mut arguments = arguments.into_iter();
+                    $(let $arg = argument::<$arg>(name, $position, arguments.next().unwrap())?;)*
+
+                    self($($arg),*)
+                        .into_result()
+                        .map_err(|msg| RuntimeErr::Err(0, msg))
+                })
+            }
+        }
+    };
+}
+impl_native_fn!();
+impl_native_fn!(A 1);
+impl_native_fn!(A 1, B 2);
+impl_native_fn!(A 1, B 2, C 3);
+impl_native_fn!(A 1, B 2, C 3, D 4);
+impl_native_fn!(A 1, B 2, C 3, D 4, E 5);
+impl_native_fn!(A 1, B 2, C 3, D 4, E 5, G 6);
+
+fn argument<T: FromRox>(name: &str, position: usize, value: Value) -> Result<T, RuntimeErr> {
+    match T::from_rox(&value) {
+        Some(argument) => Ok(argument),
+        None => {
+            let msg = format!(
+                "'{}' verwachtte een {} als argument {}, maar kreeg '{}'.",
+                name,
+                T::type_name(),
+                position,
+                value
+            );
+            Err(RuntimeErr::Err(0, msg))
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct NativeFunction {
+    name: String,
+    arity: usize,
+    function: Rc<Erased>,
+}
+impl NativeFunction {
+    pub fn new<Args>(name: &str, function: impl NativeFn<Args>) -> Self {
+        Self {
+            name: name.to_string(),
+            arity: function.arity(),
+            function: function.erase(),
+        }
+    }
+}
+impl Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NativeFunction({})", self.name)
+    }
+}
+impl Callable for NativeFunction {
+    fn call(&self, arguments: Vec<Value>, _: &mut Interpreter) -> Result<Value, RuntimeErr> {
+        (self.function)(&self.name, arguments)
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn to_string(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{engine::Engine, error::EvalError};
+
+    #[test]
+    fn register_fn() {
+        let mut engine = Engine::new();
+        engine.register_fn("wortel", f64::sqrt);
+        engine.register_fn("groet", |naam: String, luid: bool| {
+            let groet = format!("hallo {}", naam);
+            if luid {
+                groet.to_uppercase()
+            } else {
+                groet
+            }
+        });
+        engine.register_fn("som", |getallen: Vec<f64>| getallen.iter().sum::<f64>());
+        engine.register_fn("eerste", |lijst: Vec<String>| lijst.first().cloned());
+        engine.register_fn("ofNul", |getal: Option<f64>| getal.unwrap_or(0.));
+        engine.register_fn("deel", |a: f64, b: f64| {
+            if b == 0. {
+                Err("Je kan niet door nul delen.".to_string())
+            } else {
+                Ok(a / b)
+            }
+        });
+
+        let cases = [
+            ("wortel(16)", "4"),
+            ("groet(\"wereld\", wellus)", "HALLO WERELD"),
+            ("som([1, 2.5, 3])", "6.5"),
+            ("eerste([\"a\", \"b\"])", "a"),
+            ("eerste([])", "niks"),
+            ("ofNul(niks) + ofNul(2)", "2"),
+            ("deel(1, 4)", "0.25"),
+        ];
+        for (expr, expected) in cases {
+            let value = engine.eval(&format!("geef {};", expr)).unwrap();
+            assert_eq!(value.to_string(), expected, "{}", expr);
+        }
+
+        let errors = [
+            ("wortel(1, 2)", "Verwachtte 1 argumenten maar kreeg er 2."),
+            (
+                "groet(\"wereld\", 1)",
+                "'groet' verwachtte een waarheidswaarde als argument 2, maar kreeg '1'.",
+            ),
+            (
+                "som([1, \"twee\"])",
+                "'som' verwachtte een lijst van getal als argument 1, maar kreeg '[1, twee]'.",
+            ),
+            ("deel(1, 0)", "Je kan niet door nul delen."),
+        ];
+        for (expr, expected) in errors {
+            let source = format!("\n geef {};", expr);
+            let Err(EvalError::Runtime(error)) = engine.eval(&source) else {
+                panic!("Expected a runtime error for {}.", expr);
+            };
+            assert_eq!(error.message, expected);
+            assert_eq!(error.line, 2);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+    rc::Rc,
+};
+
+// output kept in memory, for tests and for hosts that want to do something with it.
+// Clones share their contents, so one can be handed over as the output and another
+// read afterwards
+#[derive(Debug, Clone, Default)]
+pub struct Buffer(Rc<RefCell<Vec<u8>>>);
+impl Buffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.borrow()).into_owned()
+    }
+}
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Engine;
+
+    #[test]
+    fn engine_output() {
+        let output = Buffer::new();
+        let mut engine = Engine::new();
+        engine.set_output(output.clone());
+
+        engine.eval("voor i van 0 tot 3 zeg i;").unwrap();
+        engine.eval("roep \"!\";").unwrap();
+        assert_eq!(output.contents(), "012!\n");
+    }
+}
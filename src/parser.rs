@@ -24,8 +24,8 @@ impl Parser {
     }
 
     fn matches(&mut self, t: Vec<TokenType>) -> bool {
-        for i in 0..t.len() {
-            if self.check(t[i]) {
+        for kind in t {
+            if self.check(kind) {
                 self.advance();
                 return true;
             }
@@ -49,7 +49,7 @@ impl Parser {
     }
 
     fn is_at_end(&self) -> bool {
-        self.peek().kind == TokenType::EOF
+        self.peek().kind == TokenType::Eof
     }
 
     fn peek(&self) -> Token {
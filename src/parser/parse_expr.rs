@@ -1,6 +1,11 @@
 use super::Parser;
 
-use crate::{error::RoxError, expr::Expr, token::Literal, token_type::TokenType};
+use crate::{
+    error::RoxError,
+    expr::{Expr, Pattern},
+    token::{Literal, Token},
+    token_type::TokenType,
+};
 
 impl Parser {
     pub fn parse_expr(&mut self) -> Result<Expr, RoxError> {
@@ -12,25 +17,39 @@ impl Parser {
     }
 
     fn assignment(&mut self) -> Result<Expr, RoxError> {
-        let expr = self.or()?;
+        let expr = self.conditional()?;
 
         if self.matches(vec![TokenType::Equal]) {
             let equals = self.previous();
             let value = self.assignment()?;
 
             match &expr {
-                Expr::Var(name) => return Ok(Expr::AssignToExpr(name.clone(), Box::new(value))),
+                Expr::Var(name) => return Ok(Expr::Assign(name.clone(), Box::new(value))),
                 Expr::Element {
                     var,
                     index,
-                    right_bracket: _,
+                    right_bracket,
                 } => {
                     return Ok(Expr::AssignToElement {
                         var: var.clone(),
                         index: index.clone(),
+                        right_bracket: right_bracket.clone(),
                         value: Box::new(value),
                     });
                 }
+                Expr::Get { object, name } => {
+                    return Ok(Expr::Set {
+                        object: object.clone(),
+                        name: name.clone(),
+                        value: Box::new(value),
+                    });
+                }
+                Expr::List(_) => {
+                    return Ok(Expr::Destructure {
+                        pattern: Box::new(self.to_pattern(expr, &equals)?),
+                        value: Box::new(value),
+                    })
+                }
                 _ => {
                     let err = RoxError::ParseError {
                         line: equals.line,
@@ -44,13 +63,70 @@ impl Parser {
         Ok(expr)
     }
 
+    // turns the left side of an assignment like '[a, b[0]] = ...' into a pattern
+    pub fn to_pattern(&self, expr: Expr, equals: &Token) -> Result<Pattern, RoxError> {
+        match expr {
+            Expr::Var(_) | Expr::Element { .. } | Expr::Get { .. } => Ok(Pattern::Target(expr)),
+            Expr::List(elements) => {
+                let mut patterns = Vec::new();
+                for element in elements {
+                    patterns.push(self.to_pattern(element, equals)?);
+                }
+                Ok(Pattern::List {
+                    elements: patterns,
+                    token: equals.clone(),
+                })
+            }
+            _ => Err(RoxError::ParseError {
+                line: equals.line,
+                msg: "Hier kan je niet aan assignen.".to_string(),
+            }),
+        }
+    }
+
+    // als condition dan expression anders expression
+    pub fn conditional(&mut self) -> Result<Expr, RoxError> {
+        if self.matches(vec![TokenType::If]) {
+            let keyword = self.previous();
+            let condition = self.expression()?;
+            self.consume(TokenType::Then, "Verwachtte 'dan' na de voorwaarde.")?;
+            let then_branch = self.expression()?;
+            self.consume(
+                TokenType::Else,
+                "Verwachtte 'anders' na de 'dan' expressie.",
+            )?;
+            let else_branch = self.conditional()?;
+
+            return Ok(Expr::Conditional {
+                keyword,
+                condition: Box::new(condition),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            });
+        }
+
+        self.coalesce()
+    }
+
+    fn coalesce(&mut self) -> Result<Expr, RoxError> {
+        let mut left = self.or()?;
+
+        while self.matches(vec![TokenType::QuestionQuestion]) {
+            let op = self.previous();
+            let right = self.or()?;
+            left = Expr::Logic(Box::new(left), op, Box::new(right));
+        }
+
+        Ok(left)
+    }
+
     fn or(&mut self) -> Result<Expr, RoxError> {
         let mut left = self.and()?;
 
         while self.matches(vec![TokenType::Or]) {
             let op = self.previous();
             let right = self.and()?;
-            left =  Expr::Logic(Box::new(left), op, Box::new(right));
+            left = Expr::Logic(Box::new(left), op, Box::new(right));
         }
 
         Ok(left)
@@ -62,7 +138,7 @@ impl Parser {
         while self.matches(vec![TokenType::And]) {
             let op = self.previous();
             let right = self.equality()?;
-            left =  Expr::Logic(Box::new(left), op, Box::new(right));
+            left = Expr::Logic(Box::new(left), op, Box::new(right));
         }
 
         Ok(left)
@@ -81,7 +157,7 @@ impl Parser {
     }
 
     fn comparison(&mut self) -> Result<Expr, RoxError> {
-        let mut expr = self.term()?;
+        let mut expr = self.bit_or()?;
 
         while self.matches(vec![
             TokenType::Greater,
@@ -89,6 +165,54 @@ impl Parser {
             TokenType::Less,
             TokenType::LessEqual,
         ]) {
+            let op = self.previous();
+            let right = self.bit_or()?;
+            expr = Expr::Binary(Box::new(expr), op, Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    fn bit_or(&mut self) -> Result<Expr, RoxError> {
+        let mut expr = self.bit_xor()?;
+
+        while self.matches(vec![TokenType::Pipe]) {
+            let op = self.previous();
+            let right = self.bit_xor()?;
+            expr = Expr::Binary(Box::new(expr), op, Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    fn bit_xor(&mut self) -> Result<Expr, RoxError> {
+        let mut expr = self.bit_and()?;
+
+        while self.matches(vec![TokenType::Tilde]) {
+            let op = self.previous();
+            let right = self.bit_and()?;
+            expr = Expr::Binary(Box::new(expr), op, Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    fn bit_and(&mut self) -> Result<Expr, RoxError> {
+        let mut expr = self.shift()?;
+
+        while self.matches(vec![TokenType::Ampersand]) {
+            let op = self.previous();
+            let right = self.shift()?;
+            expr = Expr::Binary(Box::new(expr), op, Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    fn shift(&mut self) -> Result<Expr, RoxError> {
+        let mut expr = self.term()?;
+
+        while self.matches(vec![TokenType::LessLess, TokenType::GreaterGreater]) {
             let op = self.previous();
             let right = self.term()?;
             expr = Expr::Binary(Box::new(expr), op, Box::new(right));
@@ -112,7 +236,12 @@ impl Parser {
     fn factor(&mut self) -> Result<Expr, RoxError> {
         let mut expr = self.unary()?;
 
-        while self.matches(vec![TokenType::Star, TokenType::Slash]) {
+        while self.matches(vec![
+            TokenType::Star,
+            TokenType::Slash,
+            TokenType::Percent,
+            TokenType::Backslash,
+        ]) {
             let op = self.previous();
             let right = self.unary()?;
             expr = Expr::Binary(Box::new(expr), op, Box::new(right));
@@ -121,74 +250,97 @@ impl Parser {
         Ok(expr)
     }
 
+    // unary operators bind looser than '^', so -2^2 is -(2^2)
     fn unary(&mut self) -> Result<Expr, RoxError> {
-        if self.matches(vec![TokenType::Bang, TokenType::Minus]) {
+        if self.matches(vec![TokenType::Bang, TokenType::Minus, TokenType::Tilde]) {
             let op = self.previous();
-            let right = self.power()?;
+            let right = self.unary()?;
             return Ok(Expr::Unary(op, Box::new(right)));
         }
 
         self.power()
     }
 
+    // '^' is right associative, so 2^3^2 is 2^(3^2). The exponent may have a sign, e.g. 2^-1
     fn power(&mut self) -> Result<Expr, RoxError> {
-        let mut expr = self.list()?;
+        let expr = self.call()?;
 
-        while self.matches(vec![TokenType::Caret]) {
+        if self.matches(vec![TokenType::Caret]) {
             let op = self.previous();
-            let right = self.list()?;
-            expr = Expr::Binary(Box::new(expr), op, Box::new(right));
+            let right = self.unary()?;
+            return Ok(Expr::Binary(Box::new(expr), op, Box::new(right)));
         }
 
         Ok(expr)
     }
 
-    fn list(&mut self) -> Result<Expr, RoxError> {
-        let mut elements = Vec::new();
+    // calls and indexing can be chained to any depth, e.g. f(x)[0](y)[1][2]
+    fn call(&mut self) -> Result<Expr, RoxError> {
+        let mut expr = self.primary()?;
 
-        if self.matches(vec![TokenType::LeftBracket]) {
-            elements.push(self.expression()?);
-            while self.matches(vec![TokenType::Comma]) {
-                elements.push(self.expression()?);
+        loop {
+            if self.matches(vec![TokenType::LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.matches(vec![TokenType::LeftBracket]) {
+                expr = self.finish_index(expr)?;
+            } else if self.matches(vec![TokenType::Dot]) {
+                let name = self.consume(TokenType::Identifier, "Verwachtte een naam na '.'.")?;
+                expr = Expr::Get {
+                    object: Box::new(expr),
+                    name,
+                };
+            } else {
+                break;
             }
-
-            self.consume(TokenType::RightBracket, "Verwachtte ']' na elementen")?;
-            return Ok(Expr::List(elements));
         }
 
-        self.index()
+        Ok(expr)
     }
 
-    fn index(&mut self) -> Result<Expr, RoxError> {
-        let mut var = self.call()?;
+    // either an index like 'lijst[i]' or a slice like 'lijst[start:end]'
+    fn finish_index(&mut self, var: Expr) -> Result<Expr, RoxError> {
+        let mut start = None;
+        if !self.check(TokenType::Colon) {
+            start = Some(Box::new(self.expression()?));
+        }
 
-        if self.matches(vec![TokenType::LeftBracket]) {
-            let index = self.expression()?;
-            let right_bracket = self.consume(TokenType::RightBracket, "Verwachtte ']' na index")?;
+        if self.matches(vec![TokenType::Colon]) {
+            let mut end = None;
+            if !self.check(TokenType::RightBracket) {
+                end = Some(Box::new(self.expression()?));
+            }
+            let right_bracket = self.consume(TokenType::RightBracket, "Verwachtte ']' na slice")?;
 
-            var = Expr::Element {
+            return Ok(Expr::Slice {
                 var: Box::new(var),
-                index: Box::new(index),
+                start,
+                end,
                 right_bracket,
-            };
+            });
         }
 
-        Ok(var)
+        let right_bracket = self.consume(TokenType::RightBracket, "Verwachtte ']' na index")?;
+        Ok(Expr::Element {
+            var: Box::new(var),
+            index: start.unwrap(),
+            right_bracket,
+        })
     }
 
-    fn call(&mut self) -> Result<Expr, RoxError> {
-        let mut expr = self.primary()?;
+    fn list(&mut self) -> Result<Expr, RoxError> {
+        let mut elements = Vec::new();
 
-        loop {
-            if self.matches(vec![TokenType::LeftParen]) {
-                expr = self.finish_call(expr)?;
-            } else {
-                break;
+        if !self.check(TokenType::RightBracket) {
+            elements.push(self.expression()?);
+            while self.matches(vec![TokenType::Comma]) {
+                elements.push(self.expression()?);
             }
         }
 
-        Ok(expr)
+        self.consume(TokenType::RightBracket, "Verwachtte ']' na elementen")?;
+        Ok(Expr::List(elements))
     }
+
     fn finish_call(&mut self, callee: Expr) -> Result<Expr, RoxError> {
         let mut arguments = Vec::new();
 
@@ -217,10 +369,18 @@ impl Parser {
             return Ok(Expr::Var(self.previous()));
         }
 
+        if self.matches(vec![TokenType::This]) {
+            return Ok(Expr::This(self.previous()));
+        }
+
         if self.matches(vec![TokenType::Number, TokenType::String]) {
             return Ok(Expr::Lit(self.previous().literal));
         }
 
+        if self.matches(vec![TokenType::LeftBracket]) {
+            return self.list();
+        }
+
         if self.matches(vec![TokenType::LeftParen]) {
             let expr = self.expression()?;
             self.consume(
@@ -241,3 +401,123 @@ impl Parser {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::Parser, scanner::Scanner};
+
+    fn parse(source: &str) -> String {
+        let tokens = Scanner::new(source.to_string()).scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        parser.parse_expr().unwrap().to_string()
+    }
+
+    fn assert_parses(cases: &[(&str, &str)]) {
+        for (source, expected) in cases {
+            assert_eq!(parse(source), *expected, "while parsing {}", source);
+        }
+    }
+
+    #[test]
+    fn power() {
+        assert_parses(&[
+            ("2 ^ 3 ^ 2", "(^ 2 (^ 3 2))"),
+            ("-2 ^ 2", "(- (^ 2 2))"),
+            ("2 ^ -1", "(^ 2 (- 1))"),
+            ("-2 ^ -2 ^ 2", "(- (^ 2 (- (^ 2 2))))"),
+            ("(-2) ^ 2", "(^ (group (- 2)) 2)"),
+            ("2 * 3 ^ 2", "(* 2 (^ 3 2))"),
+            ("f(2) ^ x[1]", "(^ (call f 2) (index x 1))"),
+        ]);
+    }
+
+    #[test]
+    fn unary() {
+        assert_parses(&[
+            ("!!!nietus", "(! (! (! false)))"),
+            ("--1", "(- (- 1))"),
+            ("~1 & 2", "(& (~ 1) 2)"),
+            ("-a * b", "(* (- a) b)"),
+            ("!a == b", "(== (! a) b)"),
+        ]);
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_parses(&[
+            ("1 + 2 * 3", "(+ 1 (* 2 3))"),
+            ("1 - 2 - 3", "(- (- 1 2) 3)"),
+            ("1 / 2 * 3", "(* (/ 1 2) 3)"),
+            ("1 + 7 % 3", "(+ 1 (% 7 3))"),
+            ("1 - 7 \\ 3", "(- 1 (\\ 7 3))"),
+            ("(1 + 2) * 3", "(* (group (+ 1 2)) 3)"),
+        ]);
+    }
+
+    #[test]
+    fn bitwise() {
+        assert_parses(&[
+            ("1 + 2 << 3", "(<< (+ 1 2) 3)"),
+            ("1 << 2 >> 3", "(>> (<< 1 2) 3)"),
+            ("1 & 2 << 3", "(& 1 (<< 2 3))"),
+            ("1 ~ 2 & 3", "(~ 1 (& 2 3))"),
+            ("1 | 2 ~ 3", "(| 1 (~ 2 3))"),
+            ("1 | 2 < 3", "(< (| 1 2) 3)"),
+        ]);
+    }
+
+    #[test]
+    fn comparison_and_logic() {
+        assert_parses(&[
+            ("1 < 2 == 3 >= 4", "(== (< 1 2) (>= 3 4))"),
+            ("1 <= 2 != 3 > 4", "(!= (<= 1 2) (> 3 4))"),
+            ("a == b en c", "(en (== a b) c)"),
+            ("a of b en c", "(of a (en b c))"),
+            ("a en b of c en d", "(of (en a b) (en c d))"),
+        ]);
+    }
+
+    #[test]
+    fn conditional_and_coalesce() {
+        assert_parses(&[
+            ("a ?? b of c", "(?? a (of b c))"),
+            ("a ?? b ?? c", "(?? (?? a b) c)"),
+            ("als a dan b anders c ?? d", "(als a b (?? c d))"),
+            (
+                "als a dan b anders als c dan d anders e",
+                "(als a b (als c d e))",
+            ),
+            ("als a of b dan 1 + 2 anders 3", "(als (of a b) (+ 1 2) 3)"),
+            ("x = als a dan b anders c", "(= x (als a b c))"),
+        ]);
+    }
+
+    #[test]
+    fn assignment() {
+        assert_parses(&[
+            ("a = b = 1 + 2", "(= a (= b (+ 1 2)))"),
+            ("a[1] = b of c", "(= (index a 1) (of b c))"),
+            ("a = [1, 2 * 3]", "(= a (list 1 (* 2 3)))"),
+            ("[a, [b[0], c]] = d", "(= [a [(index b 0) c]] d)"),
+            ("m[i][j] = 0", "(= (index (index m i) j) 0)"),
+            (
+                "f()[0] = g(1)[2](3)",
+                "(= (index (call f) 0) (call (index (call g 1) 2) 3))",
+            ),
+            ("[1, 2][0]", "(index (list 1 2) 0)"),
+            ("a[-1]", "(index a (- 1))"),
+            ("[]", "(list)"),
+            ("p.x", "(. x p)"),
+            ("dit.a.b(1)[0]", "(index (call (. b (. a dit)) 1) 0)"),
+            ("p.x = q.y = 1", "(= (. x p) (= (. y q) 1))"),
+            (
+                "[p.x, l[0]] = [1, 2]",
+                "(= [(. x p) (index l 0)] (list 1 2))",
+            ),
+            ("a[1:n - 1][0]", "(index (slice a 1 (- n 1)) 0)"),
+            ("a[:2]", "(slice a _ 2)"),
+            ("a[-2:]", "(slice a (- 2) _)"),
+            ("a[:]", "(slice a _ _)"),
+        ]);
+    }
+}
@@ -1,35 +1,31 @@
+use std::rc::Rc;
+
 use crate::{
     callable::FunDeclaration,
-    error::{rox_error, RoxError},
-    expr::Expr,
+    error::{Diagnostic, RoxError},
+    expr::{Expr, Pattern},
     parser::Parser,
     stmt::{If, Stmt},
-    token::Literal,
+    token::{Literal, Token},
     token_type::TokenType,
 };
 
 impl Parser {
-    pub fn parse_statements(&mut self) -> Option<Vec<Stmt>> {
-        let mut parse_error_found = false;
+    pub fn parse_statements(&mut self) -> Result<Vec<Stmt>, Vec<Diagnostic>> {
+        let mut errors = Vec::new();
         let mut statements = Vec::new();
 
         while !self.is_at_end() {
             match self.declaration() {
                 Ok(declaration) => statements.push(declaration),
-                Err(e) => {
-                    match e {
-                        RoxError::ParseError { line, msg } => rox_error(line, &msg),
-                        _ => panic!("Unreachable."),
-                    }
-                    parse_error_found = true;
-                }
+                Err(RoxError::ParseError { line, msg }) => errors.push(Diagnostic::new(line, msg)),
             }
         }
 
-        if parse_error_found {
-            None
+        if errors.is_empty() {
+            Ok(statements)
         } else {
-            Some(statements)
+            Err(errors)
         }
     }
 
@@ -44,12 +40,15 @@ impl Parser {
                 TokenType::Class
                 | TokenType::Fun
                 | TokenType::Var
+                | TokenType::Const
                 | TokenType::For
                 | TokenType::If
                 | TokenType::While
                 | TokenType::Print
                 | TokenType::Println
-                | TokenType::Return => return,
+                | TokenType::Return
+                | TokenType::Yield
+                | TokenType::Test => return,
                 _ => (),
             }
 
@@ -58,7 +57,7 @@ impl Parser {
     }
 
     pub fn declaration(&mut self) -> Result<Stmt, RoxError> {
-        if self.matches(vec![TokenType::Var]) {
+        if self.matches(vec![TokenType::Var, TokenType::Const]) {
             match self.var_declaration() {
                 Ok(stmt) => Ok(stmt),
                 Err(e) => {
@@ -66,6 +65,22 @@ impl Parser {
                     Err(e)
                 }
             }
+        } else if self.matches(vec![TokenType::Class]) {
+            match self.class_declaration() {
+                Ok(stmt) => Ok(stmt),
+                Err(e) => {
+                    self.synchronize();
+                    Err(e)
+                }
+            }
+        } else if self.matches(vec![TokenType::Test]) {
+            match self.test_declaration() {
+                Ok(stmt) => Ok(stmt),
+                Err(e) => {
+                    self.synchronize();
+                    Err(e)
+                }
+            }
         } else if self.matches(vec![TokenType::Fun]) {
             match self.fun_declaration("functie") {
                 Ok(stmt) => Ok(stmt),
@@ -86,18 +101,127 @@ impl Parser {
     }
 
     fn var_declaration(&mut self) -> Result<Stmt, RoxError> {
+        let constant = self.previous().kind == TokenType::Const;
+        if self.check(TokenType::LeftBracket) {
+            return self.destructure_declaration(constant);
+        }
+
         let name = self.consume(
             TokenType::Identifier,
             "Je moet wel een naam aan de variabele geven.",
         )?;
+        let annotation = self.type_annotation()?;
 
         let mut value = Expr::Lit(Literal::Nil);
         if self.matches(vec![TokenType::Equal]) {
             value = self.expression()?;
+        } else if constant {
+            return Err(RoxError::ParseError {
+                line: name.line,
+                msg: format!("De constante '{}' moet een waarde krijgen.", name.lexeme),
+            });
         }
 
         self.consume(TokenType::Semicolon, "Je bent de ';' vergeten.")?;
-        Ok(Stmt::Var { name, expr: value })
+        Ok(Stmt::Var {
+            name,
+            annotation,
+            expr: value,
+            constant,
+        })
+    }
+
+    // the ': getal' after a variable or a parameter, or after the ')' of a function for
+    // what it gives back. Which names are types is up to the type checker
+    fn type_annotation(&mut self) -> Result<Option<Token>, RoxError> {
+        if !self.matches(vec![TokenType::Colon]) {
+            return Ok(None);
+        }
+        if self.matches(vec![TokenType::Identifier, TokenType::Nil, TokenType::Fun]) {
+            Ok(Some(self.previous()))
+        } else {
+            Err(RoxError::ParseError {
+                line: self.previous().line,
+                msg: "Verwachtte een type na ':'.".to_string(),
+            })
+        }
+    }
+
+    // laat [a, [b, c]] = expression;
+    fn destructure_declaration(&mut self, constant: bool) -> Result<Stmt, RoxError> {
+        let pattern = self.declaration_pattern()?;
+
+        self.consume(TokenType::Equal, "Verwachtte '=' na de lijst met namen.")?;
+        let expr = self.expression()?;
+
+        self.consume(TokenType::Semicolon, "Je bent de ';' vergeten.")?;
+        Ok(Stmt::Destructure {
+            pattern,
+            expr,
+            constant,
+        })
+    }
+
+    fn declaration_pattern(&mut self) -> Result<Pattern, RoxError> {
+        if self.matches(vec![TokenType::LeftBracket]) {
+            let token = self.previous();
+            let mut elements = vec![self.declaration_pattern()?];
+            while self.matches(vec![TokenType::Comma]) {
+                elements.push(self.declaration_pattern()?);
+            }
+            self.consume(TokenType::RightBracket, "Verwachtte ']' na de namen.")?;
+
+            return Ok(Pattern::List { elements, token });
+        }
+
+        let name = self.consume(
+            TokenType::Identifier,
+            "Je moet wel een naam aan de variabele geven.",
+        )?;
+        Ok(Pattern::Target(Expr::Var(name)))
+    }
+
+    // klas Naam { methode(a, b) { ... } ... }
+    fn class_declaration(&mut self) -> Result<Stmt, RoxError> {
+        let name = self.consume(
+            TokenType::Identifier,
+            "Je moet wel een naam aan de klas geven.",
+        )?;
+        self.consume(TokenType::LeftBrace, "Verwachtte '{' na de klas naam.")?;
+
+        let mut methods = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            match self.fun_declaration("methode")? {
+                Stmt::Function(method) => methods.push(method),
+                _ => panic!("Unreachable."),
+            }
+        }
+        self.consume(TokenType::RightBrace, "Verwachtte '}' na de methodes.")?;
+
+        Ok(Stmt::Class { name, methods })
+    }
+
+    // toets "naam" { ... }
+    fn test_declaration(&mut self) -> Result<Stmt, RoxError> {
+        let keyword = self.previous();
+        let name = self.consume(TokenType::String, "Verwachtte de naam van de toets.")?;
+        let Literal::Str(name) = name.literal else {
+            panic!("Unreachable.");
+        };
+        self.consume(
+            TokenType::LeftBrace,
+            "Verwachtte '{' na de naam van de toets.",
+        )?;
+
+        let body = match self.block_statement()? {
+            Stmt::Block(statements) => statements,
+            _ => panic!("Unreachable."),
+        };
+        Ok(Stmt::Test {
+            keyword,
+            name,
+            body,
+        })
     }
 
     fn fun_declaration(&mut self, kind: &str) -> Result<Stmt, RoxError> {
@@ -108,14 +232,18 @@ impl Parser {
         self.consume(TokenType::LeftParen, &msg)?;
 
         let mut params = Vec::new();
+        let mut param_types = Vec::new();
         if !self.matches(vec![TokenType::RightParen]) {
             params.push(self.consume(TokenType::Identifier, "Verwachtte parameter na comma.")?);
+            param_types.push(self.type_annotation()?);
 
             while self.matches(vec![TokenType::Comma]) {
-                params.push(self.consume(TokenType::Identifier, "Verwachtte parameter na comma.")?)
+                params.push(self.consume(TokenType::Identifier, "Verwachtte parameter na comma.")?);
+                param_types.push(self.type_annotation()?);
             }
             self.consume(TokenType::RightParen, "Verwachtte ')' na parameter.")?;
         }
+        let return_type = self.type_annotation()?;
 
         let msg = format!("Verwachtte '{{' na de {} naam.", kind);
         self.consume(TokenType::LeftBrace, &msg)?;
@@ -128,7 +256,17 @@ impl Parser {
         //dbg!(stmt);
         //}
 
-        Ok(Stmt::Function(FunDeclaration { name, params, body }))
+        // a function with a 'lever' in it is a generator
+        let is_generator = body.iter().any(Stmt::yields);
+
+        Ok(Stmt::Function(FunDeclaration {
+            name,
+            params,
+            param_types,
+            return_type,
+            body,
+            is_generator,
+        }))
     }
 
     fn statement(&mut self) -> Result<Stmt, RoxError> {
@@ -146,6 +284,8 @@ impl Parser {
             return self.for_statement();
         } else if self.matches(vec![TokenType::Return]) {
             return self.return_statement();
+        } else if self.matches(vec![TokenType::Yield]) {
+            return self.yield_statement();
         }
         self.expr_statement()
     }
@@ -162,6 +302,14 @@ impl Parser {
         Ok(Stmt::Return { keyword, expr })
     }
 
+    fn yield_statement(&mut self) -> Result<Stmt, RoxError> {
+        let keyword = self.previous();
+        let expr = self.expression()?;
+
+        self.consume(TokenType::Semicolon, "verwachtte ';' na lever statement.")?;
+        Ok(Stmt::Yield { keyword, expr })
+    }
+
     fn block_statement(&mut self) -> Result<Stmt, RoxError> {
         let mut statements = Vec::new();
         while !self.check(TokenType::RightBrace) && !self.is_at_end() {
@@ -169,21 +317,23 @@ impl Parser {
         }
 
         self.consume(TokenType::RightBrace, "je bent een '}' vergeten druiloor")?;
-        Ok(Stmt::Block(statements))
+        Ok(Stmt::Block(Rc::new(statements)))
     }
 
     fn if_statement(&mut self) -> Result<Stmt, RoxError> {
-        let first_if = If::new(self.expression()?, self.statement()?);
+        let keyword = self.previous();
+        let first_if = If::new(keyword, self.expression()?, self.statement()?);
 
         let mut else_ifs = Vec::new();
 
         let mut final_else = None;
         while self.matches(vec![TokenType::Else]) {
             if self.matches(vec![TokenType::If]) {
-                let else_if = If::new(self.expression()?, self.statement()?);
+                let keyword = self.previous();
+                let else_if = If::new(keyword, self.expression()?, self.statement()?);
                 else_ifs.push(else_if);
             } else {
-                final_else = Some(Box::new(self.statement()?));
+                final_else = Some(Rc::new(self.statement()?));
                 break;
             }
         }
@@ -196,10 +346,15 @@ impl Parser {
     }
 
     fn while_statement(&mut self) -> Result<Stmt, RoxError> {
+        let keyword = self.previous();
         let condition = self.expression()?;
-        let body = Box::new(self.statement()?);
+        let body = Rc::new(self.statement()?);
 
-        Ok(Stmt::While { condition, body })
+        Ok(Stmt::While {
+            keyword,
+            condition,
+            body,
+        })
     }
 
     fn for_statement(&mut self) -> Result<Stmt, RoxError> {
@@ -207,13 +362,23 @@ impl Parser {
             TokenType::Identifier,
             "Je moet wel een naam aan de variabele geven.",
         )?;
-        self.consume(TokenType::From, "Verwachtte 'van'.")?;
+        if self.matches(vec![TokenType::In]) {
+            let iterable = self.expression()?;
+            let body = Rc::new(self.statement()?);
+
+            return Ok(Stmt::ForEach {
+                name,
+                iterable,
+                body,
+            });
+        }
+        self.consume(TokenType::From, "Verwachtte 'van' of 'in'.")?;
 
         let start = self.expression()?;
         self.consume(TokenType::Until, "Verwachtte 'tot'.")?;
         let end = self.expression()?;
 
-        let body = Box::new(self.statement()?);
+        let body = Rc::new(self.statement()?);
 
         Ok(Stmt::For {
             name,
@@ -236,8 +401,37 @@ impl Parser {
     }
 
     fn expr_statement(&mut self) -> Result<Stmt, RoxError> {
-        let expr = self.expression()?;
+        let mut expr = self.expression()?;
+        if self.check(TokenType::Comma) {
+            expr = self.multiple_assignment(expr)?;
+        }
+
         self.consume(TokenType::Semicolon, "Je bent een ';' vergeten druiloor")?;
         Ok(Stmt::Expr(expr))
     }
+
+    // a, b = b, a;
+    fn multiple_assignment(&mut self, first: Expr) -> Result<Expr, RoxError> {
+        let mut targets = vec![first];
+        while self.matches(vec![TokenType::Comma]) {
+            targets.push(self.conditional()?);
+        }
+        let equals = self.consume(TokenType::Equal, "Verwachtte '=' na de lijst met namen.")?;
+
+        let mut values = vec![self.expression()?];
+        while self.matches(vec![TokenType::Comma]) {
+            values.push(self.expression()?);
+        }
+        // 'a, b = f();' unpacks the list that f returns
+        let value = if values.len() == 1 {
+            values.pop().unwrap()
+        } else {
+            Expr::List(values)
+        };
+
+        Ok(Expr::Destructure {
+            pattern: Box::new(self.to_pattern(Expr::List(targets), &equals)?),
+            value: Box::new(value),
+        })
+    }
 }
@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+
+// what a builtin function needs to be allowed to do besides computing things
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    ReadFiles,
+    WriteFiles,
+    Env,
+    Process,
+}
+impl Capability {
+    // finishes the sentence "dit programma mag geen ..."
+    pub fn description(&self) -> &'static str {
+        match self {
+            Capability::ReadFiles => "bestanden lezen",
+            Capability::WriteFiles => "bestanden schrijven",
+            Capability::Env => "omgevingsvariabelen lezen",
+            Capability::Process => "programma's starten",
+        }
+    }
+}
+
+// the capabilities a program has. Builtins that need a capability the program
+// doesn't have aren't there at all, using them is an error before anything runs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Permissions(HashSet<Capability>);
+impl Permissions {
+    // only builtins that compute things, like 'lengte' and 'verzamel'
+    pub fn pure() -> Self {
+        Self::default()
+    }
+
+    pub fn all() -> Self {
+        Self::pure()
+            .with(Capability::ReadFiles)
+            .with(Capability::WriteFiles)
+            .with(Capability::Env)
+            .with(Capability::Process)
+    }
+
+    pub fn with(mut self, capability: Capability) -> Self {
+        self.0.insert(capability);
+        self
+    }
+
+    pub fn allows(&self, capability: Capability) -> bool {
+        self.0.contains(&capability)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{engine::Engine, error::EvalError};
+
+    fn resolve_error(engine: &mut Engine, source: &str) -> String {
+        match engine.eval(source) {
+            Err(EvalError::Resolve(errors)) => errors[0].message.clone(),
+            _ => panic!("Expected a resolve error."),
+        }
+    }
+
+    #[test]
+    fn pure() {
+        let mut engine = Engine::new();
+        assert_eq!(
+            engine.eval("geef lengte([1, 2]);").unwrap().to_string(),
+            "2"
+        );
+
+        let cases = [
+            (
+                "lees(\"a\")",
+                "'lees' is niet toegestaan, dit programma mag geen bestanden lezen.",
+            ),
+            (
+                "regels(\"a\")",
+                "'regels' is niet toegestaan, dit programma mag geen bestanden lezen.",
+            ),
+            (
+                "schrijf(\"a\", 1)",
+                "'schrijf' is niet toegestaan, dit programma mag geen bestanden schrijven.",
+            ),
+            (
+                "omgeving(\"a\")",
+                "'omgeving' is niet toegestaan, dit programma mag geen omgevingsvariabelen lezen.",
+            ),
+            (
+                "commando(\"a\", [])",
+                "'commando' is niet toegestaan, dit programma mag geen programma's starten.",
+            ),
+        ];
+        for (expr, expected) in cases {
+            let msg = resolve_error(&mut engine, &format!("{};", expr));
+            assert_eq!(msg, expected);
+        }
+    }
+
+    #[test]
+    fn files() {
+        let path = std::env::temp_dir().join("rox_permissions_files.txt");
+        let source = format!(
+            "schrijf(\"{0}\", \"een\ntwee\"); geef lees(\"{0}\");",
+            path.display()
+        );
+
+        let permissions = Permissions::pure().with(Capability::ReadFiles);
+        let mut engine = Engine::with_permissions(&permissions);
+        let msg = resolve_error(&mut engine, &source);
+        assert_eq!(
+            msg,
+            "'schrijf' is niet toegestaan, dit programma mag geen bestanden schrijven."
+        );
+
+        let mut engine = Engine::with_permissions(&permissions.with(Capability::WriteFiles));
+        let value = engine.eval(&source).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(value.to_string(), "een\ntwee");
+    }
+
+    #[test]
+    fn env_and_process() {
+        let mut engine = Engine::with_permissions(&Permissions::all());
+        let value = engine.eval("geef omgeving(\"ROX_BESTAAT_NIET\");").unwrap();
+        assert_eq!(value.to_string(), "niks");
+
+        let value = engine
+            .eval("geef commando(\"echo\", [\"hoi\", 3]);")
+            .unwrap();
+        assert_eq!(value.to_string(), "hoi 3\n");
+    }
+}
@@ -0,0 +1,453 @@
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use crate::{
+    callable::FunDeclaration,
+    error::Diagnostic,
+    expr::Expr,
+    permissions::Capability,
+    stmt::{If, Stmt},
+    token::Token,
+};
+
+// static pass over the statements that runs before the interpreter,
+// so mistakes like assigning to a constant are reported before any code has run.
+//
+// The scoping rules it enforces:
+// - a name can only be declared once per scope, but it can shadow a name from an outer scope
+// - shadowing a builtin function such as 'lengte' or 'klok' gives a warning
+// - a variable can't be used in its own initializer
+// - outside of functions a name has to be declared before it is used. Inside a function
+//   it can also be declared later on in one of the scopes around the function
+pub struct Resolver {
+    builtins: HashSet<String>,
+    scopes: Vec<Scope>,
+    // the index of the scope that each function we're currently in was declared in
+    function_scopes: Vec<usize>,
+    // the number of classes we're currently in, 'dit' can only be used inside one
+    class_depth: usize,
+    errors: Vec<Diagnostic>,
+    pub warnings: Vec<Diagnostic>,
+    forbidden: HashMap<String, Capability>,
+    // the bodies of 'toets' blocks, they run after everything else so they are resolved last
+    tests: Vec<Rc<Vec<Stmt>>>,
+}
+
+#[derive(Default)]
+struct Scope {
+    bindings: HashMap<String, Binding>,
+    // names used inside functions that weren't declared yet when the function was resolved
+    pending: Vec<Token>,
+}
+
+struct Binding {
+    constant: bool,
+    // false while the initializer is being resolved
+    defined: bool,
+}
+
+impl Resolver {
+    pub fn new(builtins: Vec<String>) -> Self {
+        Self {
+            builtins: builtins.into_iter().collect(),
+            scopes: vec![Scope::default()],
+            function_scopes: Vec::new(),
+            class_depth: 0,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            forbidden: HashMap::new(),
+            tests: Vec::new(),
+        }
+    }
+
+    // a global that an earlier run of the same interpreter declared
+    pub fn declare_global(&mut self, name: String, constant: bool) {
+        let binding = Binding {
+            constant,
+            defined: true,
+        };
+        self.scopes[0].bindings.insert(name, binding);
+    }
+
+    // a builtin that the permissions left out
+    pub fn forbid(&mut self, name: String, capability: Capability) {
+        self.forbidden.insert(name, capability);
+    }
+
+    pub fn resolve(&mut self, statements: &Vec<Stmt>) -> Result<(), Vec<Diagnostic>> {
+        self.resolve_statements(statements);
+        for body in std::mem::take(&mut self.tests) {
+            self.begin_scope();
+            self.resolve_statements(&body);
+            self.end_scope();
+        }
+
+        for name in std::mem::take(&mut self.scopes[0].pending) {
+            if !self.scopes[0].bindings.contains_key(&name.lexeme) {
+                self.unknown_variable(&name);
+            }
+        }
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    fn resolve_statements(&mut self, statements: &Vec<Stmt>) {
+        for stmt in statements {
+            self.resolve_stmt(stmt);
+        }
+    }
+
+    fn error(&mut self, line: usize, msg: &str) {
+        self.errors.push(Diagnostic::new(line, msg));
+    }
+
+    fn unknown_variable(&mut self, name: &Token) {
+        let msg = match self.forbidden.get(&name.lexeme) {
+            Some(capability) => format!(
+                "'{}' is niet toegestaan, dit programma mag geen {}.",
+                name.lexeme,
+                capability.description()
+            ),
+            None => format!("'{}' is een onbekende variabele.", name.lexeme),
+        };
+        self.error(name.line, &msg);
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(Scope::default());
+    }
+
+    // names that are still pending might be declared later on in the surrounding scope
+    fn end_scope(&mut self) {
+        let scope = self.scopes.pop().unwrap();
+        let parent = self.scopes.last_mut().unwrap();
+
+        for name in scope.pending {
+            if !scope.bindings.contains_key(&name.lexeme) {
+                parent.pending.push(name);
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &Token, constant: bool) {
+        let scope = &mut self.scopes.last_mut().unwrap().bindings;
+        if scope.contains_key(&name.lexeme) {
+            let msg = format!("'{}' is al gedeclareerd in deze scope.", name.lexeme);
+            self.error(name.line, &msg);
+            return;
+        }
+        scope.insert(
+            name.lexeme.clone(),
+            Binding {
+                constant,
+                defined: false,
+            },
+        );
+
+        if self.builtins.contains(&name.lexeme) {
+            let msg = format!("'{}' overschaduwt een ingebouwde functie.", name.lexeme);
+            self.warnings.push(Diagnostic::new(name.line, msg));
+        }
+    }
+
+    fn define(&mut self, name: &Token) {
+        let scope = &mut self.scopes.last_mut().unwrap().bindings;
+        if let Some(binding) = scope.get_mut(&name.lexeme) {
+            binding.defined = true;
+        }
+    }
+
+    // returns the binding of the innermost scope that declares the name,
+    // reports an error if the name can't be used here
+    fn resolve_name(&mut self, name: &Token) -> Option<&Binding> {
+        let innermost = &self.scopes.last().unwrap().bindings;
+        if let Some(Binding { defined: false, .. }) = innermost.get(&name.lexeme) {
+            let msg = format!(
+                "Je kan '{}' niet gebruiken in zijn eigen declaratie.",
+                name.lexeme
+            );
+            self.error(name.line, &msg);
+            return None;
+        }
+
+        let found = self
+            .scopes
+            .iter()
+            .any(|scope| scope.bindings.contains_key(&name.lexeme));
+        if !found {
+            if !self.builtins.contains(&name.lexeme) {
+                match self.function_scopes.last() {
+                    Some(index) => self.scopes[*index].pending.push(name.clone()),
+                    None => self.unknown_variable(name),
+                }
+            }
+            return None;
+        }
+
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.bindings.get(&name.lexeme))
+    }
+
+    fn resolve_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Expr(expr) | Stmt::Print(expr) | Stmt::Println(expr) => self.resolve_expr(expr),
+
+            Stmt::Var {
+                name,
+                expr,
+                constant,
+                ..
+            } => {
+                self.declare(name, *constant);
+                self.resolve_expr(expr);
+                self.define(name);
+            }
+
+            Stmt::Destructure {
+                pattern,
+                expr,
+                constant,
+            } => {
+                let names: Vec<&Token> = pattern
+                    .targets()
+                    .into_iter()
+                    .filter_map(|target| match target {
+                        Expr::Var(name) => Some(name),
+                        _ => None,
+                    })
+                    .collect();
+
+                for name in &names {
+                    self.declare(name, *constant);
+                }
+                self.resolve_expr(expr);
+                for name in names {
+                    self.define(name);
+                }
+            }
+
+            Stmt::Block(statements) => {
+                self.begin_scope();
+                self.resolve_statements(statements);
+                self.end_scope();
+            }
+
+            Stmt::If {
+                first_if,
+                else_ifs,
+                final_else,
+            } => {
+                for If {
+                    should_execute,
+                    statement,
+                    ..
+                } in std::iter::once(first_if).chain(else_ifs)
+                {
+                    self.resolve_expr(should_execute);
+                    self.resolve_stmt(statement);
+                }
+                if let Some(final_else) = final_else {
+                    self.resolve_stmt(final_else);
+                }
+            }
+
+            Stmt::While {
+                condition, body, ..
+            } => {
+                self.resolve_expr(condition);
+                self.resolve_stmt(body);
+            }
+
+            Stmt::For {
+                name,
+                start,
+                end,
+                body,
+            } => {
+                self.resolve_expr(start);
+                self.resolve_expr(end);
+                self.resolve_loop(name, body);
+            }
+
+            Stmt::ForEach {
+                name,
+                iterable,
+                body,
+            } => {
+                self.resolve_expr(iterable);
+                self.resolve_loop(name, body);
+            }
+
+            Stmt::Function(function) => {
+                // declared before the body is resolved, so that it can call itself
+                self.declare(&function.name, false);
+                self.define(&function.name);
+                self.resolve_function(function);
+            }
+
+            Stmt::Class { name, methods } => {
+                self.declare(name, false);
+                self.define(name);
+
+                self.class_depth += 1;
+                for method in methods {
+                    self.resolve_function(method);
+                }
+                self.class_depth -= 1;
+            }
+
+            Stmt::Return { expr, .. } => self.resolve_expr(expr),
+
+            Stmt::Test { keyword, body, .. } => {
+                if self.scopes.len() > 1 {
+                    self.error(
+                        keyword.line,
+                        "'toets' kan alleen buiten functies en blokken worden gebruikt.",
+                    );
+                }
+                self.tests.push(Rc::clone(body));
+            }
+
+            Stmt::Yield { keyword, expr } => {
+                if self.function_scopes.is_empty() {
+                    self.error(
+                        keyword.line,
+                        "'lever' kan alleen binnen een proces worden gebruikt.",
+                    );
+                }
+                self.resolve_expr(expr);
+            }
+        }
+    }
+
+    fn resolve_function(&mut self, function: &FunDeclaration) {
+        self.function_scopes.push(self.scopes.len() - 1);
+        self.begin_scope();
+        for param in &function.params {
+            self.declare(param, false);
+            self.define(param);
+        }
+        self.resolve_statements(&function.body);
+        self.end_scope();
+        self.function_scopes.pop();
+    }
+
+    // the loop variable of a 'voor' loop lives in a scope around the body
+    fn resolve_loop(&mut self, name: &Token, body: &Stmt) {
+        self.begin_scope();
+        self.declare(name, false);
+        self.define(name);
+        self.resolve_stmt(body);
+        self.end_scope();
+    }
+
+    fn resolve_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Lit(_) => (),
+            Expr::Var(name) => {
+                self.resolve_name(name);
+            }
+            Expr::Grouping(expr) | Expr::Unary(_, expr) => self.resolve_expr(expr),
+            Expr::Binary(left, _, right) | Expr::Logic(left, _, right) => {
+                self.resolve_expr(left);
+                self.resolve_expr(right);
+            }
+            Expr::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.resolve_expr(condition);
+                self.resolve_expr(then_branch);
+                self.resolve_expr(else_branch);
+            }
+            Expr::Assign(name, value) => {
+                self.resolve_expr(value);
+                self.check_not_constant(name);
+            }
+            Expr::AssignToElement {
+                var, index, value, ..
+            } => {
+                self.resolve_expr(value);
+                self.resolve_expr(index);
+                self.resolve_target(var);
+            }
+            Expr::Destructure { pattern, value } => {
+                self.resolve_expr(value);
+                for target in pattern.targets() {
+                    self.resolve_target(target);
+                }
+            }
+            Expr::Call(callee, _, arguments) => {
+                self.resolve_expr(callee);
+                for argument in arguments {
+                    self.resolve_expr(argument);
+                }
+            }
+            Expr::List(elements) => {
+                for element in elements {
+                    self.resolve_expr(element);
+                }
+            }
+            Expr::Element { var, index, .. } => {
+                self.resolve_expr(var);
+                self.resolve_expr(index);
+            }
+            Expr::Get { object, .. } => self.resolve_expr(object),
+            Expr::Set { object, value, .. } => {
+                self.resolve_expr(value);
+                self.resolve_target(object);
+            }
+            Expr::This(keyword) => {
+                if self.class_depth == 0 {
+                    self.error(
+                        keyword.line,
+                        "'dit' kan alleen binnen een methode worden gebruikt.",
+                    );
+                }
+            }
+            Expr::Slice {
+                var, start, end, ..
+            } => {
+                self.resolve_expr(var);
+                for bound in [start, end].into_iter().flatten() {
+                    self.resolve_expr(bound);
+                }
+            }
+        }
+    }
+
+    // the variable that is assigned to, or the list whose element is assigned to.
+    // The elements of a constant list can't be changed either, at any depth, and neither can
+    // the properties of the instances in it
+    fn resolve_target(&mut self, target: &Expr) {
+        match target {
+            Expr::Var(name) => self.check_not_constant(name),
+            Expr::Element { var, index, .. } => {
+                self.resolve_expr(index);
+                self.resolve_target(var);
+            }
+            Expr::Get { object, .. } => self.resolve_target(object),
+            _ => self.resolve_expr(target),
+        }
+    }
+
+    fn check_not_constant(&mut self, name: &Token) {
+        if let Some(Binding { constant: true, .. }) = self.resolve_name(name) {
+            let msg = format!(
+                "'{}' is een constante en kan niet worden aangepast.",
+                name.lexeme
+            );
+            self.error(name.line, &msg);
+        }
+    }
+}
@@ -1,35 +1,72 @@
-use std::fs;
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
 use crate::{
-    error::{crash, RuntimeErr},
-    interpreter::Interpreter,
+    engine::Engine,
+    error::{rox_error, rox_warning, Diagnostic, EvalError, RoxError, RuntimeErr},
+    formatter::format,
+    interpreter::{Interpreter, DEFAULT_MAX_CALL_DEPTH},
+    output::Buffer,
     parser::Parser,
+    permissions::Permissions,
     scanner::Scanner,
+    stmt::Stmt,
     value::Value,
 };
 use colored::Colorize;
 
-const PRINT_SCAN_OUTPUT: bool = false;
 const PRINT_PARS_OUTPUT: bool = false;
 //const PRINT_INTERPRETER :bool = false;
 
+// deep recursion in Rox is deep recursion in the interpreter,
+// so Rox runs on a thread with this much stack
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+// runs Rox from the command line, reporting the errors it finds
 pub struct Rox {
     //had_error: bool,
+    max_call_depth: usize,
+    // every run gets its own writer for the output of the program
+    output: Box<dyn Fn() -> Box<dyn Write>>,
+    diagnostics: Box<dyn Write>,
 }
 impl Rox {
     pub fn new() -> Self {
         //Self { had_error: false }
-        Self {}
+        Self {
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            output: Box::new(|| Box::new(io::stdout())),
+            diagnostics: Box::new(io::stderr()),
+        }
+    }
+
+    // how deep Rox functions may call each other, STACK_SIZE fits the default
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    // where 'zeg' and 'roep' write to instead of stdout
+    pub fn set_output(&mut self, output: impl Write + Clone + 'static) {
+        self.output = Box::new(move || Box::new(output.clone()));
+    }
+
+    // where errors and warnings are written to instead of stderr
+    pub fn set_diagnostics(&mut self, diagnostics: impl Write + 'static) {
+        self.diagnostics = Box::new(diagnostics);
     }
 
     pub fn run_prompt(&mut self, source: String) {
         let mut scanner = Scanner::new(source);
         let tokens = match scanner.scan_tokens() {
             Ok(tokens) => tokens,
-            Err(_) => {
-                println!(
-                    "{}",
-                    "Scanfout(en) gedetecteerd, programma wordt gestopt.".purple()
+            Err(errors) => {
+                self.report(
+                    &errors,
+                    "Scanfout(en) gedetecteerd, programma wordt gestopt.",
                 );
                 return;
             }
@@ -38,30 +75,30 @@ impl Rox {
         let mut parser = Parser::new(tokens);
         let expr = match parser.parse_expr() {
             Ok(expr) => expr,
-            Err(_) => {
-                println!(
-                    "{}",
-                    "Parsingfout(en) gedetecteerd,  programma wordt gestopt.".purple()
+            Err(RoxError::ParseError { line, msg }) => {
+                self.report(
+                    &[Diagnostic::new(line, msg)],
+                    "Parsingfout(en) gedetecteerd,  programma wordt gestopt.",
                 );
                 return;
             }
         };
         if PRINT_PARS_OUTPUT {
-            println!("{}", expr.to_string());
+            println!("{}", expr);
         }
 
-        let mut interpreter = Interpreter::new();
-        let value = match interpreter.evaluate_expr(&expr) {
-            Ok(value) => value,
-            Err(err) => {
-                let RuntimeErr::Err(line, msg) = err else {
-                    panic!("Unreachable.");
-                };
-                crash(line, &msg);
+        let mut interpreter = Interpreter::new(&Permissions::all());
+        interpreter.max_call_depth = self.max_call_depth;
+        interpreter.output = (self.output)();
+        match interpreter.evaluate_expr(&expr) {
+            Ok(value) => {
+                let _ = writeln!(interpreter.output, "{}", value);
             }
-        };
-
-        println!("{}", value.to_string());
+            Err(RuntimeErr::Err(line, msg) | RuntimeErr::Limit(line, msg)) => {
+                self.report(&[Diagnostic::new(line, msg)], "Rentijd fout gedetecteerd.");
+            }
+            Err(_) => panic!("Unreachable."),
+        }
     }
 
     pub fn run_file(&mut self, source: &str) -> Value {
@@ -71,45 +108,268 @@ impl Rox {
     }
 
     fn run(&mut self, source: String) -> Value {
-        let mut scanner = Scanner::new(source);
+        let mut engine = Engine::with_permissions(&Permissions::all());
+        engine.set_max_call_depth(self.max_call_depth);
+        engine.interpreter.output = (self.output)();
 
-        let tokens = match scanner.scan_tokens() {
-            Ok(tokens) => tokens,
-            Err(_) => {
-                println!(
-                    "{}",
-                    "Scan error(s) detected, programma wordt gestopt.".purple()
-                );
+        let compiled = engine.compile(&source);
+        for warning in engine.warnings() {
+            rox_warning(&mut *self.diagnostics, warning.line, &warning.message);
+        }
+
+        let statements = match compiled {
+            Ok(statements) => statements,
+            Err(err) => {
+                let summary = match err {
+                    EvalError::Scan(_) => "Scan error(s) detected, programma wordt gestopt.",
+                    EvalError::Parse(_) => "Parsingfout(en) gedetecteerd, programma wordt gestopt.",
+                    EvalError::Resolve(_) => {
+                        "Resolutiefout(en) gedetecteerd, programma wordt gestopt."
+                    }
+                    EvalError::Type(_) => "Typefout(en) gedetecteerd, programma wordt gestopt.",
+                    EvalError::Runtime(_) | EvalError::Limit(_) => panic!("Unreachable."),
+                };
+                self.report(err.diagnostics(), summary);
                 return Value::Nil;
             }
         };
 
-        if PRINT_SCAN_OUTPUT {
-            for token in &tokens {
-                print!("{}_", token.to_string());
+        // unlike 'Engine::eval' the program keeps going after a runtime error
+        let (errors, return_val) = engine.interpreter.interpret(statements);
+        let _ = engine.interpreter.output.flush();
+        if !errors.is_empty() {
+            self.report(&errors, "Rentijd fout(en) gedetecteerd.");
+        }
+        return_val
+    }
+
+    // runs the 'toets' blocks of a file, or of every .rox file in a directory, and
+    // prints which ones passed. Every test gets a fresh interpreter that runs the rest
+    // of the file first, so tests can't influence each other. Returns whether all passed
+    pub fn run_tests(&mut self, path: &str) -> bool {
+        let Some(paths) = self.rox_files(path) else {
+            return false;
+        };
+
+        let mut out = (self.output)();
+        let (mut passed, mut failed) = (0, 0);
+        for path in paths {
+            let _ = writeln!(out, "{}", path.display());
+            let statements = self
+                .read(&path)
+                .and_then(|source| self.compile_tests(&source));
+            let Some(statements) = statements else {
+                failed += 1;
+                continue;
+            };
+
+            for stmt in &statements {
+                let Stmt::Test { name, body, .. } = stmt else {
+                    continue;
+                };
+                match self.run_test(&statements, body) {
+                    Ok(()) => {
+                        passed += 1;
+                        let _ = writeln!(out, "  {} {}", "ok".green(), name);
+                    }
+                    Err((diagnostic, output)) => {
+                        failed += 1;
+                        let _ = writeln!(out, "  {} {}", "FOUT".red(), name);
+                        let _ = writeln!(out, "    {}", diagnostic.to_string().red());
+                        for line in output.lines() {
+                            let _ = writeln!(out, "    | {}", line);
+                        }
+                    }
+                }
             }
-            println!();
         }
 
-        let mut parser = Parser::new(tokens);
+        let summary = format!("{} geslaagd, {} gefaald.", passed, failed);
+        let summary = if failed == 0 {
+            summary.green()
+        } else {
+            summary.red()
+        };
+        let _ = writeln!(out, "{}", summary);
+        let _ = out.flush();
+        failed == 0
+    }
 
-        let statements = match parser.parse_statements() {
-            Some(statements) => statements,
-            None => {
-                println!(
-                    "{}",
-                    "Parsingfout(en) gedetecteerd, programma wordt gestopt.".purple()
-                );
-                return Value::Nil;
+    // formats a file, or every .rox file in a directory, like 'rox fmt'. With check the
+    // files are left alone and only the ones that aren't formatted are named, for CI.
+    // Returns whether all files were formatted already or could be formatted
+    pub fn format_files(&mut self, path: &str, check: bool) -> bool {
+        let Some(paths) = self.rox_files(path) else {
+            return false;
+        };
+
+        let mut out = (self.output)();
+        let mut succeeded = true;
+        for path in paths {
+            let Some(source) = self.read(&path) else {
+                succeeded = false;
+                continue;
+            };
+            let formatted = match format(&source) {
+                Ok(formatted) => formatted,
+                Err(errors) => {
+                    let summary = format!(
+                        "Fout(en) gedetecteerd, '{}' wordt niet geformatteerd.",
+                        path.display()
+                    );
+                    self.report(&errors, &summary);
+                    succeeded = false;
+                    continue;
+                }
+            };
+            if formatted == source {
+                continue;
+            }
+
+            if check {
+                let _ = writeln!(out, "'{}' is niet geformatteerd.", path.display());
+                succeeded = false;
+            } else if let Err(err) = fs::write(&path, formatted) {
+                let msg = format!("Kon '{}' niet schrijven: {}.", path.display(), err);
+                let _ = writeln!(self.diagnostics, "{}", msg.red());
+                succeeded = false;
+            } else {
+                let _ = writeln!(out, "'{}' is geformatteerd.", path.display());
             }
+        }
+        let _ = out.flush();
+        succeeded
+    }
+
+    // looks for mistakes in a file, or every .rox file in a directory, without running
+    // it. Returns whether there were no errors or warnings
+    pub fn check_files(&mut self, path: &str) -> bool {
+        let Some(paths) = self.rox_files(path) else {
+            return false;
         };
 
-        let mut interpreter = Interpreter::new();
-        let (error_found, return_val) = interpreter.interpret(statements);
-        if error_found {
-            println!("{}", "Rentijd fout(en) gedetecteerd.".purple());
+        let mut out = (self.output)();
+        let mut succeeded = true;
+        for path in paths {
+            let Some(source) = self.read(&path) else {
+                succeeded = false;
+                continue;
+            };
+
+            let mut engine = Engine::with_permissions(&Permissions::all());
+            match engine.check(&source) {
+                Ok(warnings) if warnings.is_empty() => {
+                    let _ = writeln!(out, "{} {}", "ok".green(), path.display());
+                }
+                Ok(warnings) => {
+                    let _ = writeln!(out, "{} {}", "LET OP".yellow(), path.display());
+                    let _ = out.flush();
+                    for warning in warnings {
+                        rox_warning(&mut *self.diagnostics, warning.line, &warning.message);
+                    }
+                    succeeded = false;
+                }
+                Err(err) => {
+                    let _ = writeln!(out, "{} {}", "FOUT".red(), path.display());
+                    let _ = out.flush();
+                    self.report(err.diagnostics(), "Fout(en) gedetecteerd.");
+                    succeeded = false;
+                }
+            }
         }
-        return_val
+        let _ = out.flush();
+        succeeded
+    }
+
+    // the file, or the .rox files in the directory
+    fn rox_files(&mut self, path: &str) -> Option<Vec<PathBuf>> {
+        match rox_files(Path::new(path)) {
+            Ok(paths) => Some(paths),
+            Err(err) => {
+                let msg = format!("Kon '{}' niet lezen: {}.", path, err);
+                let _ = writeln!(self.diagnostics, "{}", msg.red());
+                None
+            }
+        }
+    }
+
+    fn read(&mut self, path: &Path) -> Option<String> {
+        match fs::read_to_string(path) {
+            Ok(source) => Some(source),
+            Err(err) => {
+                let msg = format!("Kon '{}' niet lezen: {}.", path.display(), err);
+                let _ = writeln!(self.diagnostics, "{}", msg.red());
+                None
+            }
+        }
+    }
+
+    fn compile_tests(&mut self, source: &str) -> Option<Vec<Stmt>> {
+        let mut engine = Engine::with_permissions(&Permissions::all());
+        let compiled = engine.compile(source);
+        for warning in engine.warnings() {
+            rox_warning(&mut *self.diagnostics, warning.line, &warning.message);
+        }
+
+        match compiled {
+            Ok(statements) => Some(statements),
+            Err(err) => {
+                self.report(
+                    err.diagnostics(),
+                    "Fout(en) gedetecteerd, toetsen worden overgeslagen.",
+                );
+                None
+            }
+        }
+    }
+
+    // returns the error and what the test printed if it failed
+    fn run_test(
+        &self,
+        statements: &[Stmt],
+        body: &Rc<Vec<Stmt>>,
+    ) -> Result<(), (Diagnostic, String)> {
+        let output = Buffer::new();
+        let mut engine = Engine::with_permissions(&Permissions::all());
+        engine.set_max_call_depth(self.max_call_depth);
+        engine.set_output(output.clone());
+
+        let result = engine
+            .interpreter
+            .run(statements)
+            .and_then(|_| engine.interpreter.run(&[Stmt::Block(Rc::clone(body))]));
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err((err.diagnostics()[0].clone(), output.contents())),
+        }
+    }
+
+    fn report(&mut self, diagnostics: &[Diagnostic], summary: &str) {
+        for diagnostic in diagnostics {
+            rox_error(&mut *self.diagnostics, diagnostic.line, &diagnostic.message);
+        }
+        let _ = writeln!(self.diagnostics, "{}", summary.purple());
+    }
+}
+fn rox_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "rox") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+impl Default for Rox {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -148,11 +408,11 @@ mod tests {
         let value = lox.run(source);
 
         let num = match value {
-            Value::Num(num) => num,
-            _ => panic!("Expected Num."),
+            Value::Int(num) => num,
+            _ => panic!("Expected Int."),
         };
 
-        assert_eq!(num, 10.);
+        assert_eq!(num, 10);
     }
 
     #[test]
@@ -169,11 +429,11 @@ mod tests {
         let value = lox.run(source);
 
         let num = match value {
-            Value::Num(num) => num,
-            _ => panic!("Expected Num."),
+            Value::Int(num) => num,
+            _ => panic!("Expected Int."),
         };
 
-        assert_eq!(num, 9.);
+        assert_eq!(num, 9);
     }
 
     #[test]
@@ -190,11 +450,11 @@ mod tests {
         let value = lox.run(source);
 
         let num = match value {
-            Value::Num(num) => num,
-            _ => panic!("Expected Num."),
+            Value::Int(num) => num,
+            _ => panic!("Expected Int."),
         };
 
-        assert_eq!(num, 1.);
+        assert_eq!(num, 1);
     }
 
     #[test]
@@ -236,11 +496,11 @@ mod tests {
         let value = lox.run(source);
 
         let num = match value {
-            Value::Num(num) => num,
-            _ => panic!("Expected num."),
+            Value::Int(num) => num,
+            _ => panic!("Expected Int."),
         };
 
-        assert_eq!(num, 8.);
+        assert_eq!(num, 8);
     }
 
     #[test]
@@ -256,11 +516,11 @@ mod tests {
         let value = lox.run(source);
 
         let num = match value {
-            Value::Num(num) => num,
-            _ => panic!("Expected num."),
+            Value::Int(num) => num,
+            _ => panic!("Expected Int."),
         };
 
-        assert_eq!(num, 3.);
+        assert_eq!(num, 3);
     }
 
     #[test]
@@ -274,10 +534,569 @@ mod tests {
         let value = lox.run(source);
 
         let num = match value {
-            Value::Num(num) => num,
-            _ => panic!("Expected num."),
+            Value::Int(num) => num,
+            _ => panic!("Expected Int."),
         };
 
-        assert_eq!(num, 21.);
+        assert_eq!(num, 21);
+    }
+
+    #[test]
+    fn integer_arithmetic() {
+        let source = "
+        geef [7 \\ 2, -7 \\ 2, 7 % 3, -7 % 3, 2 ^ 10, 7 / 2];"
+            .to_string();
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+
+        assert_eq!(value.to_string(), "[3, -4, 1, 2, 1024, 3.5]");
+    }
+
+    #[test]
+    fn integer_overflow() {
+        let source = "
+        laat x = 9223372036854775807;
+        geef [x + 1, -x - 2, (x + 1) - 1, -(2 ^ 100) \\ 7, -(2 ^ 100) % 7, 3 ^ 80 \\ (2 ^ 70 + 5), 3 ^ 80 % (2 ^ 70 + 5)];"
+            .to_string();
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+
+        assert_eq!(
+            value.to_string(),
+            "[9223372036854775808, -9223372036854775809, 9223372036854775807, \
+            -181092942889747057356671886483, 5, 125198948409041546, 1110568068841910036367]"
+        );
+    }
+
+    #[test]
+    fn big_fact_and_fib() {
+        let source = "
+        geef [fact(50), fib(200)];"
+            .to_string();
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+
+        assert_eq!(
+            value.to_string(),
+            "[30414093201713378043612608166064768844377641568960512000000000000, \
+            280571172992510140037611932413038677189525]"
+        );
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        let source = "
+        geef [6 & 3, 6 | 3, 6 ~ 3, ~5, 1 << 70, -5 >> 1, -(1 << 70) | 1, 1 + 2 << 3, 5 & 3 == 1];"
+            .to_string();
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+
+        assert_eq!(
+            value.to_string(),
+            "[2, 7, 5, -6, 1180591620717411303424, -3, -1180591620717411303423, 24, wellus]"
+        );
+    }
+
+    #[test]
+    fn conditional_and_coalesce() {
+        let source = "
+        laat x = niks;
+        laat a = [\"a\"][0];
+        laat y = als wellus dan 1 anders a - 1;
+        geef [x ?? 5, 3 ?? a - 1, y, als 1 > 2 dan \"ja\" anders \"nee\"];"
+            .to_string();
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+
+        assert_eq!(value.to_string(), "[5, 3, 1, nee]");
+    }
+
+    #[test]
+    fn constants() {
+        let source = "
+        vast x = [1, 2];
+        proces verander() {
+            x = 3;
+        }
+        geef x;"
+            .to_string();
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+
+        // assigning to a constant is found before running, so nothing is returned
+        assert!(matches!(value, Value::Nil));
+
+        let source = "
+        vast x = [1, 2];
+        proces verander(lijst) {
+            lijst[0] = 3;
+            geef lijst;
+        }
+        laat kopie = x;
+        kopie[1] = 4;
+        geef [x, kopie, verander(x)];"
+            .to_string();
+
+        let value = lox.run(source);
+        assert_eq!(value.to_string(), "[[1, 2], [1, 4], [3, 2]]");
+    }
+
+    // instances are shared, so 'vast' also covers the properties of the instances in it
+    #[test]
+    fn constant_instances() {
+        let mut lox = Rox::new();
+        let errors = [
+            "klas K {} vast l = [K()]; l[0].x = 3; geef l[0].x;",
+            "klas K {} vast k = K(); k.x = 3; geef k.x;",
+            "klas K { nieuw() { dit.l = [1]; } } vast k = K(); k.l[0] = 2; geef k.l;",
+            "klas K {} vast k = K(); k.x, k.y = 1, 2; geef k.x;",
+        ];
+        for source in errors {
+            assert!(matches!(lox.run(source.to_string()), Value::Nil));
+        }
+
+        let source = "klas K {} laat l = [K()]; l[0].x = 3; geef l[0].x;";
+        assert_eq!(lox.run(source.to_string()).to_string(), "3");
+    }
+
+    #[test]
+    fn scoping_rules() {
+        let mut lox = Rox::new();
+
+        // functions can use names that are declared after them
+        let source = "
+        proces even(n) {
+            als n == 0 geef wellus;
+            geef oneven(n - 1);
+        }
+        proces oneven(n) {
+            als n == 0 geef nietus;
+            geef even(n - 1);
+        }
+        laat x = 1;
+        {
+            laat x = 2;
+        }
+        geef even(10);"
+            .to_string();
+        assert!(matches!(lox.run(source), Value::True));
+
+        let errors = [
+            "laat x = 1; laat x = 2;",
+            "proces f(a, a) {}",
+            "{ laat x = x + 1; }",
+            "roep y; laat y = 1;",
+            "y = 1;",
+            "proces f() { geef z; }",
+        ];
+        for source in errors {
+            let source = format!("{} geef 1;", source);
+            assert!(matches!(lox.run(source), Value::Nil));
+        }
+    }
+
+    #[test]
+    fn destructuring() {
+        let source = "
+        proces deel(a, b) {
+            geef [a \\ b, a % b];
+        }
+        laat [q, r] = deel(17, 5);
+        vast [x, [y, z]] = [1, [2, 3]];
+        laat a = \"a\";
+        laat b = \"b\";
+        a, b = b, a;
+        laat lijst = [0, 0];
+        [lijst[1], q] = [q, r];
+        geef [q, r, x, y, z, a, b, lijst];"
+            .to_string();
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+
+        assert_eq!(value.to_string(), "[2, 2, 1, 2, 3, b, a, [0, 3]]");
+
+        // arity mismatches are runtime errors
+        let source = "
+        laat [a, b] = [1, 2, 3];
+        geef a;"
+            .to_string();
+        assert!(matches!(lox.run(source), Value::Nil));
+    }
+
+    #[test]
+    fn nested_indexing() {
+        let source = "
+        proces matrix() {
+            geef [[1, 2], [3, [4, 5]]];
+        }
+        proces keer(n) {
+            geef [matrix, keer];
+        }
+        laat m = matrix();
+        m[0][1] = 0;
+        m[1][1][0] = m[1][1][1] * 2;
+        matrix()[0] = 7;
+        geef [m, matrix()[1][1][0], keer(1)[1](2)[0]()[0], [5, 6][1]];"
+            .to_string();
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+
+        assert_eq!(value.to_string(), "[[[1, 0], [3, [10, 5]]], 4, [1, 2], 6]");
+    }
+
+    #[test]
+    fn negative_indices_and_slices() {
+        let source = "
+        laat l = [1, 2, 3, 4, 5];
+        l[-1] = 50;
+        laat m = [[1, 2], [3, 4]];
+        m[-1][-2] = 30;
+        geef [l[-1], l[-5], l[1:3], l[:2], l[3:], l[-2:], l[:-3], l[:], l[4:1], l[-100:100], m];"
+            .to_string();
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+
+        assert_eq!(
+            value.to_string(),
+            "[50, 1, [2, 3], [1, 2], [4, 50], [4, 50], [1, 2], [1, 2, 3, 4, 50], [], [1, 2, 3, 4, 50], [[1, 2], [30, 4]]]"
+        );
+
+        let errors = [
+            "[1, 2][-3]",
+            "[1, 2][2]",
+            "[1, 2][1.5]",
+            "[1, 2][\"1\"]",
+            "[1, 2][0.5:]",
+            "[1, 2][99999999999999999999]",
+        ];
+        for source in errors {
+            let source = format!("geef {};", source);
+            assert!(matches!(lox.run(source), Value::Nil));
+        }
+    }
+
+    #[test]
+    fn structural_equality_and_ordering() {
+        let source = "
+        proces f() {}
+        laat g = f;
+        geef [
+            [1, [2, 3]] == [1, [2, 3]], [1, 2] == [1, 2, 3], [1] != [1.0], f == g, f == lengte,
+            \"appel\" < \"peer\", \"b\" > \"abc\", \"ab\" <= \"ab\",
+            [1, 2] < [1, 3], [1, 2] < [1, 2, 0], [[2]] > [[1, 5]], [] >= [], [1, \"a\"] < [1, \"b\"]
+        ];"
+        .to_string();
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+
+        assert_eq!(
+            value.to_string(),
+            "[wellus, nietus, nietus, wellus, nietus, wellus, wellus, wellus, wellus, wellus, wellus, wellus, wellus]"
+        );
+
+        let errors = [
+            "1 < \"a\"",
+            "[1] < \"a\"",
+            "wellus < nietus",
+            "niks >= niks",
+        ];
+        for source in errors {
+            let source = format!("geef {};", source);
+            assert!(matches!(lox.run(source), Value::Nil));
+        }
+    }
+
+    // 2^53 + 1 can't be a float, so going through f64 would make it equal to 2^53
+    #[test]
+    fn exact_number_comparison() {
+        let source = "
+        laat a = 9007199254740992;
+        laat b = 9007199254740993;
+        laat c = 9007199254740992.0;
+        laat nan = 0 / 0;
+        klas K { f() { geef 1; } }
+        laat [k1, k2] = [K(), K()];
+        geef [
+            a < b, b == c, a == c, b > c, [b] > [c], c < b, -0.5 > -1, 1 == 1.0,
+            nan < 1, [1] < [nan], k1.f == k2.f, k1.f == k1.f
+        ];"
+        .to_string();
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+
+        assert_eq!(
+            value.to_string(),
+            "[wellus, nietus, wellus, wellus, wellus, wellus, wellus, wellus, nietus, wellus, nietus, wellus]"
+        );
+    }
+
+    #[test]
+    fn generators() {
+        let source = "
+        proces naturals() {
+            laat n = 0;
+            terwijl wellus {
+                lever n;
+                n = n + 1;
+            }
+        }
+        proces kwadraten(getallen) {
+            voor n in getallen {
+                als n % 2 == 0 {
+                    laat kwadraat = n * n;
+                    lever kwadraat;
+                }
+            }
+        }
+        proces totDrie() {
+            voor i van 1 tot 10 {
+                als i > 3 geef;
+                lever i;
+            }
+        }
+        laat g = totDrie();
+        laat eerste = volgende(g);
+        laat som = 0;
+        voor x in g som = som + x;
+        laat letters = \"\";
+        voor c in \"abc\" letters = c + letters;
+        geef [neem(kwadraten(naturals()), 4), eerste, som, volgende(g), verzamel(totDrie()),
+              lengte(kwadraten([1, 2, 3, 4])), letters, g];"
+            .to_string();
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+
+        assert_eq!(
+            value.to_string(),
+            "[[0, 4, 16, 36], 1, 5, niks, [1, 2, 3], 2, cba, <generator totDrie>]"
+        );
+
+        let errors = [
+            "lever 1; geef 1;",
+            "geef verzamel(5);",
+            "proces f() { lever volgende(g); } laat g = f(); geef volgende(g);",
+        ];
+        for source in errors {
+            assert!(matches!(lox.run(source.to_string()), Value::Nil));
+        }
+    }
+
+    #[test]
+    fn streaming_lines() {
+        let path = std::env::temp_dir().join("rox_streaming_lines.txt");
+        std::fs::write(&path, "een\ntwee\ndrie\n").unwrap();
+
+        let source = format!(
+            "
+            laat aantal = 0;
+            laat regel = niks;
+            voor r in regels(\"{}\") {{
+                aantal = aantal + 1;
+                regel = r;
+            }}
+            geef [aantal, regel];",
+            path.display()
+        );
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(value.to_string(), "[3, drie]");
+    }
+
+    #[test]
+    fn operator_overloading() {
+        let source = "
+        klas Geld {
+            nieuw(centen) {
+                dit.centen = centen;
+            }
+            plus(ander) { geef Geld(dit.centen + ander.centen); }
+            min(ander) { geef Geld(dit.centen - ander.centen); }
+            keer(factor) { geef Geld(dit.centen * factor); }
+            gelijk(ander) { geef dit.centen == ander.centen; }
+            kleiner(ander) { geef dit.centen < ander.centen; }
+            index(i) { geef [dit.centen \\ 100, dit.centen % 100][i]; }
+        }
+        laat a = Geld(250);
+        laat b = Geld(199);
+        laat c = a;
+        c.centen = 300;
+        geef [(a + b).centen, (a - b * 2).centen, a == Geld(300), a != Geld(300), b < a, a[0], a[-1],
+              Geld(1) == Geld(2), a];"
+            .to_string();
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+
+        assert_eq!(
+            value.to_string(),
+            "[499, -98, wellus, nietus, wellus, 3, 0, nietus, <instantie van Geld>]"
+        );
+
+        let errors = [
+            "klas A {} geef A() + 1;",
+            "klas A {} geef A()[0];",
+            "klas A { gelijk() { geef wellus; } } geef A() == A();",
+            "klas A { kleiner(b) { geef 1; } } geef A() < A();",
+            "klas A {} geef A().x;",
+            "proces f() { geef dit; } geef 1;",
+        ];
+        for source in errors {
+            assert!(matches!(lox.run(source.to_string()), Value::Nil));
+        }
+    }
+
+    #[test]
+    fn tail_calls() {
+        let source = "
+        proces aftellen(n, stappen) {
+            als n == 0 geef stappen;
+            geef aftellen(n - 1, stappen + 1);
+        }
+        proces som(n, totaal) {
+            geef als n == 0 dan totaal anders (som(n - 1, totaal + n));
+        }
+        proces faculteit(n) {
+            als n <= 1 geef 1;
+            geef n * faculteit(n - 1);
+        }
+        geef [aftellen(1000000, 0), som(100000, 0), faculteit(20)];"
+            .to_string();
+
+        let mut lox = Rox::new();
+        let value = lox.run(source);
+
+        assert_eq!(
+            value.to_string(),
+            "[1000000, 5000050000, 2432902008176640000]"
+        );
+    }
+
+    #[test]
+    fn recursion_depth_limit() {
+        // the default depth needs more stack than a test thread has
+        let handle = std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(|| {
+                let mut lox = Rox::new();
+                let source = "proces f(n) { geef 1 + f(n + 1); } geef f(0);";
+                assert!(matches!(lox.run(source.to_string()), Value::Nil));
+
+                let source = "
+                proces diepte(n) {
+                    als n == 0 geef 0;
+                    geef 1 + diepte(n - 1);
+                }";
+                lox.set_max_call_depth(10);
+                let value = lox.run(format!("{} geef diepte(9);", source));
+                assert_eq!(value.to_string(), "9");
+                let value = lox.run(format!("{} geef diepte(10);", source));
+                assert!(matches!(value, Value::Nil));
+
+                // generators count as a call while they run
+                let source = "
+                proces nest(n) {
+                    voor x in nest(n + 1) lever x;
+                }
+                geef verzamel(nest(0));";
+                assert!(matches!(lox.run(source.to_string()), Value::Nil));
+            })
+            .unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn output_and_diagnostics() {
+        let output = Buffer::new();
+        let diagnostics = Buffer::new();
+        let mut lox = Rox::new();
+        lox.set_output(output.clone());
+        lox.set_diagnostics(diagnostics.clone());
+
+        let source = "
+            zeg \"een \";
+            roep 1 + 1;
+            roep [1][5];
+            roep \"drie\";";
+        lox.run(source.to_string());
+
+        assert_eq!(output.contents(), "een 2\ndrie\n");
+        let diagnostics = diagnostics.contents();
+        assert!(diagnostics.contains("Index 5 valt buiten een lijst met lengte 1."));
+        assert!(diagnostics.contains("Rentijd fout(en) gedetecteerd."));
+    }
+
+    #[test]
+    fn run_tests() {
+        let path = std::env::temp_dir().join("rox_run_tests.rox");
+        let source = "
+            laat teller = 0;
+            proces verhoog() {
+                teller = teller + 1;
+                geef teller;
+            }
+
+            toets \"eerste keer\" {
+                verzekerGelijk(1, verhoog());
+            }
+
+            // every test starts over, so this is 1 again
+            toets \"tweede keer\" {
+                verzekerGelijk(1, verhoog());
+            }
+
+            toets \"faalt\" {
+                roep \"bijna\";
+                verzekerGelijk(2, verhoog());
+            }";
+        fs::write(&path, source).unwrap();
+
+        let output = Buffer::new();
+        let mut lox = Rox::new();
+        lox.set_output(output.clone());
+        let passed = lox.run_tests(path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+
+        assert!(!passed);
+        let output = output.contents();
+        assert!(output.contains("eerste keer"));
+        assert!(output.contains("tweede keer"));
+        assert!(output.contains("[line 19] Verwachtte '2' maar kreeg '1'."));
+        assert!(output.contains("| bijna"));
+        assert!(output.contains("2 geslaagd, 1 gefaald."));
+    }
+
+    #[test]
+    fn format_files() {
+        let path = std::env::temp_dir().join("rox_format_files.rox");
+        fs::write(&path, "laat x=1;").unwrap();
+        let path_str = path.to_str().unwrap();
+
+        let output = Buffer::new();
+        let mut lox = Rox::new();
+        lox.set_output(output.clone());
+        assert!(!lox.format_files(path_str, true));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "laat x=1;");
+        assert!(output.contents().contains("is niet geformatteerd."));
+
+        assert!(lox.format_files(path_str, false));
+        assert!(lox.format_files(path_str, true));
+        let formatted = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(formatted, "laat x = 1;\n");
     }
 }
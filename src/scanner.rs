@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 
-use crate::error::{crash, rox_error, RoxError};
-use crate::token::{Literal, Token};
+use crate::bigint::BigInt;
+use crate::error::Diagnostic;
+use crate::token::{Comment, Literal, Token};
 use crate::token_type::TokenType;
 
 pub struct Scanner {
@@ -12,7 +13,8 @@ pub struct Scanner {
     start: usize,
     current: usize,
     line: usize,
-    had_error: bool,
+    errors: Vec<Diagnostic>,
+    comments: Vec<Comment>,
 }
 
 impl Scanner {
@@ -27,9 +29,10 @@ impl Scanner {
         }
 
         let keywords = create_keywords!(
-            "en",And "of",Or "als",If "anders",Else "terwijl",While "voor",For "tot",Until
+            "en",And "of",Or "als",If "dan",Then "anders",Else "terwijl",While "voor",For "tot",Until
             "wellus",True "nietus",False "niks",Nil "dit",This "ouder",Super "van",From
-            "klas",Class "proces",Fun "laat",Var "geef",Return "zeg",Print "roep", Println
+            "klas",Class "proces",Fun "laat",Var "vast",Const "geef",Return "zeg",Print "roep", Println
+            "lever",Yield "in",In "toets",Test
         );
 
         Self {
@@ -39,30 +42,39 @@ impl Scanner {
             start: 0,
             current: 0,
             line: 1,
-            had_error: false,
+            errors: Vec::new(),
+            comments: Vec::new(),
         }
     }
 
-    pub fn scan_tokens(&mut self) -> Result<Vec<Token>, RoxError> {
+    pub fn scan_tokens(&mut self) -> Result<Vec<Token>, Vec<Diagnostic>> {
         while !self.at_end_input() {
             self.start = self.current;
             self.scan_token();
         }
 
         self.tokens.push(Token::new(
-            TokenType::EOF,
+            TokenType::Eof,
             "".to_string(),
             Literal::None,
             self.line,
         ));
 
-        if self.had_error {
-            Err(RoxError::ScanError)
+        if !self.errors.is_empty() {
+            Err(std::mem::take(&mut self.errors))
         } else {
             Ok(self.tokens.clone())
         }
     }
 
+    pub fn comments(&self) -> Vec<Comment> {
+        self.comments.clone()
+    }
+
+    fn error(&mut self, msg: &str) {
+        self.errors.push(Diagnostic::new(self.line, msg));
+    }
+
     fn at_end_input(&self) -> bool {
         self.current >= self.source.len()
     }
@@ -91,17 +103,46 @@ impl Scanner {
             '[' => self.add_token(TokenType::LeftBracket),
             ']' => self.add_token(TokenType::RightBracket),
             ',' => self.add_token(TokenType::Comma),
+            ':' => self.add_token(TokenType::Colon),
             '.' => self.add_token(TokenType::Dot),
             '-' => self.add_token(TokenType::Minus),
             '+' => self.add_token(TokenType::Plus),
             ';' => self.add_token(TokenType::Semicolon),
             '*' => self.add_token(TokenType::Star),
+            '%' => self.add_token(TokenType::Percent),
+            '\\' => self.add_token(TokenType::Backslash),
+            '&' => self.add_token(TokenType::Ampersand),
+            '|' => self.add_token(TokenType::Pipe),
+            '~' => self.add_token(TokenType::Tilde),
             '^' => self.add_token(TokenType::Caret),
 
+            '?' => {
+                if self.matches('?') {
+                    self.current += 1;
+                    self.add_token(TokenType::QuestionQuestion);
+                } else {
+                    self.error("Verwachtte '??'.");
+                }
+            }
+
             '!' => ternary!('=', BangEqual, Bang),
             '=' => ternary!('=', EqualEqual, Equal),
-            '<' => ternary!('=', LessEqual, Less),
-            '>' => ternary!('=', GreaterEqual, Greater),
+            '<' => {
+                if self.matches('<') {
+                    self.current += 1;
+                    self.add_token(TokenType::LessLess);
+                } else {
+                    ternary!('=', LessEqual, Less)
+                }
+            }
+            '>' => {
+                if self.matches('>') {
+                    self.current += 1;
+                    self.add_token(TokenType::GreaterGreater);
+                } else {
+                    ternary!('=', GreaterEqual, Greater)
+                }
+            }
 
             // comments
             '/' => {
@@ -109,11 +150,13 @@ impl Scanner {
                     while self.peek() != '\n' && !self.at_end_input() {
                         self.current += 1;
                     }
+                    self.add_comment(self.line);
                 } else if self.matches('*') {
+                    let line = self.line;
                     self.check_for_end_comment();
+                    self.add_comment(line);
                 } else {
                     self.add_token(TokenType::Slash);
-                    self.current += 1;
                 }
             }
 
@@ -126,7 +169,8 @@ impl Scanner {
                     self.current += 1;
                 }
                 if self.at_end_input() {
-                    crash(self.line, "Ongetermineerde reeks.");
+                    self.error("Ongetermineerde reeks.");
+                    return;
                 }
 
                 self.current += 1;
@@ -157,8 +201,7 @@ impl Scanner {
                     self.add_token(kind);
                 } else {
                     let msg = format!("'{}' is een ongeldig karakter.", c);
-                    rox_error(self.line,&msg);
-                    self.had_error = true;
+                    self.error(&msg);
                 }
             }
         }
@@ -202,6 +245,16 @@ impl Scanner {
         self.source.as_bytes()[self.current + 1] as char
     }
 
+    fn add_comment(&mut self, line: usize) {
+        let trailing = self.tokens.last().is_some_and(|token| token.line == line);
+        self.comments.push(Comment {
+            text: self.source[self.start..self.current].to_string(),
+            line,
+            before: self.tokens.len(),
+            trailing,
+        });
+    }
+
     fn add_lit_token(&mut self, kind: TokenType, lit: Literal) {
         let text = self.source[self.start..self.current].to_string();
         self.tokens.push(Token::new(kind, text, lit, self.line));
@@ -216,21 +269,29 @@ impl Scanner {
             self.current += 1;
         }
 
+        // literals without a decimal point are integers
         if self.peek() == '.' && self.peek_next().is_ascii_digit() {
             self.current += 1;
 
             while self.peek().is_ascii_digit() {
                 self.current += 1;
             }
-        }
 
-        let num = self.source[self.start..self.current].to_string();
-        let num = num.parse::<f64>().unwrap();
-        self.add_lit_token(TokenType::Number, Literal::Num(num))
+            let num = self.source[self.start..self.current].to_string();
+            let num = num.parse::<f64>().unwrap();
+            self.add_lit_token(TokenType::Number, Literal::Num(num))
+        } else {
+            let int = &self.source[self.start..self.current];
+            let lit = match int.parse::<i64>() {
+                Ok(int) => Literal::Int(int),
+                Err(_) => Literal::Big(BigInt::parse(int).unwrap()),
+            };
+            self.add_lit_token(TokenType::Number, lit)
+        }
     }
 
     fn peek_next(&self) -> char {
-        if self.current >= self.source.len() {
+        if self.current + 1 >= self.source.len() {
             '\0'
         } else {
             self.get_next_char()
@@ -1,5 +1,7 @@
+use std::rc::Rc;
+
 use crate::callable::FunDeclaration;
-use crate::expr::Expr;
+use crate::expr::{Expr, Pattern};
 use crate::token::Token;
 
 #[derive(Debug, Clone)]
@@ -9,41 +11,115 @@ pub enum Stmt {
     Println(Expr),
     Var {
         name: Token,
+        // the type after ':', checked before the program runs
+        annotation: Option<Token>,
+        expr: Expr,
+        constant: bool,
+    },
+    Destructure {
+        pattern: Pattern,
         expr: Expr,
+        constant: bool,
     },
-    Block(Vec<Stmt>),
+    // bodies are reference counted, so a suspended generator can hold on to them cheaply
+    Block(Rc<Vec<Stmt>>),
     If {
         first_if: If,
         else_ifs: Vec<If>,
-        final_else: Option<Box<Stmt>>,
+        final_else: Option<Rc<Stmt>>,
     },
     While {
+        keyword: Token,
         condition: Expr,
-        body: Box<Stmt>,
+        body: Rc<Stmt>,
     },
     For {
         name: Token,
         start: Expr,
         end: Expr,
-        body: Box<Stmt>,
+        body: Rc<Stmt>,
+    },
+    // voor name in iterable
+    ForEach {
+        name: Token,
+        iterable: Expr,
+        body: Rc<Stmt>,
     },
     Function(FunDeclaration),
+    Class {
+        name: Token,
+        methods: Vec<FunDeclaration>,
+    },
     Return {
         keyword: Token,
         expr: Expr,
     },
+    Yield {
+        keyword: Token,
+        expr: Expr,
+    },
+    // toets "name" { ... }, only runs with 'rox test'
+    Test {
+        keyword: Token,
+        name: String,
+        body: Rc<Vec<Stmt>>,
+    },
+}
+impl Stmt {
+    // whether this statement contains a 'lever', not counting the bodies of nested functions
+    pub fn yields(&self) -> bool {
+        match self {
+            Stmt::Yield { .. } => true,
+            Stmt::Block(statements) => statements.iter().any(Stmt::yields),
+            Stmt::If {
+                first_if,
+                else_ifs,
+                final_else,
+            } => {
+                std::iter::once(first_if)
+                    .chain(else_ifs)
+                    .any(|branch| branch.statement.yields())
+                    || final_else.as_ref().is_some_and(|stmt| stmt.yields())
+            }
+            Stmt::While { body, .. } | Stmt::For { body, .. } | Stmt::ForEach { body, .. } => {
+                body.yields()
+            }
+            _ => false,
+        }
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Stmt::Expr(expr) | Stmt::Print(expr) | Stmt::Println(expr) => expr.line(),
+            Stmt::Destructure { expr, .. } => expr.line(),
+            Stmt::Block(statements) => statements.iter().find_map(Stmt::line),
+            Stmt::If { first_if, .. } => Some(first_if.keyword.line),
+            Stmt::Function(function) => Some(function.name.line),
+            Stmt::Var { name, .. }
+            | Stmt::For { name, .. }
+            | Stmt::ForEach { name, .. }
+            | Stmt::Class { name, .. }
+            | Stmt::Return { keyword: name, .. }
+            | Stmt::Yield { keyword: name, .. }
+            | Stmt::While { keyword: name, .. }
+            | Stmt::Test { keyword: name, .. } => Some(name.line),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct If {
+    // the 'als'
+    pub keyword: Token,
     pub should_execute: Expr,
-    pub statement: Box<Stmt>,
+    pub statement: Rc<Stmt>,
 }
 impl If {
-    pub fn new(should_execute: Expr, statement: Stmt) -> Self {
+    pub fn new(keyword: Token, should_execute: Expr, statement: Stmt) -> Self {
         If {
+            keyword,
             should_execute,
-            statement: Box::new(statement),
+            statement: Rc::new(statement),
         }
     }
 }
@@ -1,11 +1,13 @@
 use std::fmt::Display;
 
-use crate::token_type::TokenType;
+use crate::{bigint::BigInt, token_type::TokenType};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
     None,
     Str(String),
+    Int(i64),
+    Big(BigInt),
     Num(f64),
     True,
     False,
@@ -16,6 +18,8 @@ impl Display for Literal {
         match self {
             Literal::None => write!(f, "None"),
             Literal::Str(s) => write!(f, "{}", s),
+            Literal::Int(i) => write!(f, "{}", i),
+            Literal::Big(b) => write!(f, "{}", b),
             Literal::Num(n) => write!(f, "{}", n),
             Literal::True => write!(f, "true"),
             Literal::False => write!(f, "false"),
@@ -40,15 +44,27 @@ impl Token {
             line,
         }
     }
-
-    pub fn to_string(&self) -> String {
+}
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         //format!("{:?}{}{}", self.kind, self.lexeme, self.literal)
         match &self.literal {
-            Literal::Str(s) => s.clone(),
-            Literal::Num(n) => n.to_string(),
-            _ => self.lexeme.clone(),
-            /* _ => "".to_string(), */
+            Literal::Str(s) => write!(f, "{}", s),
+            Literal::Int(i) => write!(f, "{}", i),
+            Literal::Big(b) => write!(f, "{}", b),
+            Literal::Num(n) => write!(f, "{}", n),
+            _ => write!(f, "{}", self.lexeme),
         }
-        //self.lexeme.clone()
     }
 }
+
+// the scanner keeps comments aside for 'rox fmt', the parser never sees them
+#[derive(Clone, Debug)]
+pub struct Comment {
+    pub text: String,
+    pub line: usize,
+    // the index of the token that comes after the comment
+    pub before: usize,
+    // whether a token comes before it on the same line
+    pub trailing: bool,
+}
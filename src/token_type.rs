@@ -11,12 +11,18 @@ pub enum TokenType {
     LeftBracket,
     RightBracket,
     Comma,
+    Colon,
     Dot,
     Minus,
     Plus,
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Backslash,
+    Ampersand,
+    Pipe,
+    Tilde,
 
     // One or two character tokens.
     Bang,
@@ -27,6 +33,9 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    LessLess,
+    GreaterGreater,
+    QuestionQuestion,
 
     // Literals.
     Identifier,
@@ -41,16 +50,21 @@ pub enum TokenType {
     Fun,
     For,
     If,
+    Then,
     Nil,
     Or,
     Print,
     Println,
     Return,
+    Yield,
+    In,
+    Test,
     Super,
     This,
     True,
     Var,
+    Const,
     While,
     //
-    EOF,
+    Eof,
 }
@@ -1,42 +1,54 @@
-use crate::{callable::Callable, token::Literal};
+use std::{cmp::Ordering, fmt::Display};
+
+use crate::{
+    bigint::BigInt,
+    callable::Callable,
+    class::Instance,
+    iterator::{ListIter, RoxIterator, StrIter},
+    token::Literal,
+};
 
 #[derive(Debug, Clone)]
 pub enum Value {
     Nil,
     True,
     False,
+    Int(i64),
+    Big(BigInt),
     Num(f64),
     Str(String),
     Callable(Box<dyn Callable>),
     List(Vec<Value>),
+    Iterator(RoxIterator),
+    Instance(Instance),
 }
-impl Value {
-    pub fn to_string(&self) -> String {
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Value::Nil => "niks".to_string(),
-            Value::True => "wellus".to_string(),
-            Value::False => "nietus".to_string(),
-            Value::Num(num) => num.to_string(),
-            Value::Str(str) => str.to_string(),
-            Value::Callable(callee) => format!("<fn {}>", callee.to_string()),
+            Value::Nil => write!(f, "niks"),
+            Value::True => write!(f, "wellus"),
+            Value::False => write!(f, "nietus"),
+            Value::Int(int) => write!(f, "{}", int),
+            Value::Big(big) => write!(f, "{}", big),
+            Value::Num(num) => write!(f, "{}", num),
+            Value::Str(str) => write!(f, "{}", str),
+            Value::Callable(callee) => write!(f, "<fn {}>", callee.to_string()),
             Value::List(elements) => {
-                let mut out = "[".to_string();
-                for i in 0..(elements.len() - 1) {
-                    out.push_str(&elements[i].to_string());
-                    out.push_str(", ");
-                }
-
-                out.push_str(&elements[elements.len() - 1].to_string());
-                out.push(']');
-                out
+                let elements: Vec<String> = elements.iter().map(|e| e.to_string()).collect();
+                write!(f, "[{}]", elements.join(", "))
             }
+            Value::Iterator(iterator) => write!(f, "<{}>", iterator),
+            Value::Instance(instance) => write!(f, "{}", instance),
         }
     }
-
+}
+impl Value {
     pub fn from_lit(lit: &Literal) -> Self {
         match lit {
             Literal::None => panic!("Unreachable."),
             Literal::Str(str) => Value::Str(str.clone()),
+            Literal::Int(int) => Value::Int(*int),
+            Literal::Big(big) => Value::Big(big.clone()),
             Literal::Num(num) => Value::Num(*num),
             Literal::True => Value::True,
             Literal::False => Value::False,
@@ -59,14 +71,183 @@ impl Value {
         }
     }
 
+    // integers that don't fit in 64 bits are stored as a BigInt,
+    // every integer that does fit is stored as an Int
+    pub fn from_big(big: BigInt) -> Value {
+        match big.to_i64() {
+            Some(int) => Value::Int(int),
+            None => Value::Big(big),
+        }
+    }
+
+    pub fn as_big(&self) -> Option<BigInt> {
+        match self {
+            Value::Int(int) => Some(BigInt::from(*int)),
+            Value::Big(big) => Some(big.clone()),
+            _ => None,
+        }
+    }
+
+    // integers are promoted to floats when they are combined with a float
+    pub fn as_num(&self) -> Option<f64> {
+        match self {
+            Value::Int(int) => Some(*int as f64),
+            Value::Big(big) => Some(big.to_f64()),
+            Value::Num(num) => Some(*num),
+            _ => None,
+        }
+    }
+
     pub fn is_equal(value1: &Value, value2: &Value) -> bool {
         match (value1, value2) {
             (Value::Nil, Value::Nil) => true,
             (Value::True, Value::True) => true,
             (Value::False, Value::False) => true,
+            (Value::Int(int1), Value::Int(int2)) => int1 == int2,
+            (Value::Int(_) | Value::Big(_), Value::Int(_) | Value::Big(_)) => {
+                value1.as_big() == value2.as_big()
+            }
             (Value::Num(num1), Value::Num(num2)) => num1 == num2,
+            (Value::Int(_) | Value::Big(_), Value::Num(num)) => {
+                compare_with_float(&value1.as_big().unwrap(), *num) == Some(Ordering::Equal)
+            }
+            (Value::Num(num), Value::Int(_) | Value::Big(_)) => {
+                compare_with_float(&value2.as_big().unwrap(), *num) == Some(Ordering::Equal)
+            }
             (Value::Str(str1), Value::Str(str2)) => str1 == str2,
+            (Value::List(elements1), Value::List(elements2)) => {
+                elements1.len() == elements2.len()
+                    && elements1
+                        .iter()
+                        .zip(elements2)
+                        .all(|(element1, element2)| Value::is_equal(element1, element2))
+            }
+            (Value::Callable(callee1), Value::Callable(callee2)) => {
+                Value::same_callable(callee1.as_ref(), callee2.as_ref())
+            }
+            (Value::Iterator(iterator1), Value::Iterator(iterator2)) => {
+                iterator1.id() == iterator2.id()
+            }
+            // instances can define their own equality with a 'gelijk' method,
+            // which the interpreter calls for '=='
+            (Value::Instance(instance1), Value::Instance(instance2)) => {
+                instance1.id() == instance2.id()
+            }
             _ => false,
         }
     }
+
+    // two callables are the same if they have the same name and were declared on the same
+    // line, methods also have to be bound to the same instance
+    fn same_callable(callee1: &dyn Callable, callee2: &dyn Callable) -> bool {
+        callee1.to_string() == callee2.to_string()
+            && callee1.line() == callee2.line()
+            && callee1.instance_id() == callee2.instance_id()
+    }
+
+    // a total order over all values, so any list of values can be sorted:
+    //   niks < nietus < wellus < numbers < strings < lists < functions < iterators < instances
+    // - numbers are ordered by their exact value, whether they are integers or floats.
+    //   NaN comes after every other number
+    // - strings are ordered character by character
+    // - lists are ordered element by element, a list comes before any longer list it is a prefix of
+    // - functions are ordered by their name, and then by the line they were declared on.
+    //   Methods of different instances are ordered like iterators
+    // - iterators are only equal to themselves, their order is arbitrary but doesn't change
+    // - instances are ordered by the name of their class, and then like iterators
+    //
+    // Values that are equal according to 'is_equal' are also equal here, except for NaN
+    pub fn compare(&self, other: &Value) -> Ordering {
+        let rank = self.rank().cmp(&other.rank());
+        if rank != Ordering::Equal {
+            return rank;
+        }
+
+        match (self, other) {
+            (Value::Int(int1), Value::Int(int2)) => int1.cmp(int2),
+            (Value::Int(_) | Value::Big(_), Value::Int(_) | Value::Big(_)) => {
+                self.as_big().cmp(&other.as_big())
+            }
+            (Value::Num(num1), Value::Num(num2)) => match num1.partial_cmp(num2) {
+                Some(ordering) => ordering,
+                None => num1.is_nan().cmp(&num2.is_nan()),
+            },
+            (Value::Int(_) | Value::Big(_), Value::Num(num)) => {
+                compare_with_float(&self.as_big().unwrap(), *num).unwrap_or(Ordering::Less)
+            }
+            (Value::Num(num), Value::Int(_) | Value::Big(_)) => {
+                compare_with_float(&other.as_big().unwrap(), *num)
+                    .map_or(Ordering::Greater, Ordering::reverse)
+            }
+            (Value::Str(str1), Value::Str(str2)) => str1.cmp(str2),
+            (Value::List(elements1), Value::List(elements2)) => {
+                for (element1, element2) in elements1.iter().zip(elements2) {
+                    let ordering = element1.compare(element2);
+                    if ordering != Ordering::Equal {
+                        return ordering;
+                    }
+                }
+                elements1.len().cmp(&elements2.len())
+            }
+            (Value::Callable(callee1), Value::Callable(callee2)) => callee1
+                .to_string()
+                .cmp(&callee2.to_string())
+                .then_with(|| callee1.line().cmp(&callee2.line()))
+                .then_with(|| callee1.instance_id().cmp(&callee2.instance_id())),
+            (Value::Iterator(iterator1), Value::Iterator(iterator2)) => {
+                iterator1.id().cmp(&iterator2.id())
+            }
+            (Value::Instance(instance1), Value::Instance(instance2)) => instance1
+                .class_name()
+                .cmp(&instance2.class_name())
+                .then_with(|| instance1.id().cmp(&instance2.id())),
+            _ => Ordering::Equal,
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Value::Nil => 0,
+            Value::False => 1,
+            Value::True => 2,
+            Value::Int(_) | Value::Big(_) | Value::Num(_) => 3,
+            Value::Str(_) => 4,
+            Value::List(_) => 5,
+            Value::Callable(_) => 6,
+            Value::Iterator(_) => 7,
+            Value::Instance(_) => 8,
+        }
+    }
+
+    // lists and strings are iterated over from the start, iterators continue where they are
+    pub fn iter(&self) -> Option<RoxIterator> {
+        match self {
+            Value::List(elements) => Some(RoxIterator::new(ListIter::new(elements.clone()))),
+            Value::Str(str) => Some(RoxIterator::new(StrIter::new(str))),
+            Value::Iterator(iterator) => Some(iterator.clone()),
+            _ => None,
+        }
+    }
+}
+
+// compares an integer with a float exactly, going through f64 would round big integers.
+// None if the float is NaN
+fn compare_with_float(int: &BigInt, num: f64) -> Option<Ordering> {
+    if num.is_nan() {
+        return None;
+    }
+    if num.is_infinite() {
+        return Some(if num > 0. {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        });
+    }
+
+    // an integer equal to the floor of the float is still smaller if the float has a fraction
+    let floor = num.floor();
+    match int.cmp(&BigInt::from_f64(floor).unwrap()) {
+        Ordering::Equal if floor < num => Some(Ordering::Less),
+        ordering => Some(ordering),
+    }
 }